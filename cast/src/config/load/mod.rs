@@ -47,6 +47,9 @@ impl Config {
             FileType::Hcl => Config::hcl(file_path)?,
             // FileType::Pkl => Config::pkl(file_path)?,
         };
+        // Source of the file for rule error spans
+        let src = fs::read_to_string(file_path).into_diagnostic()?;
+        config.check_rules(&src)?;
         config.strict_check()
     }
     /**
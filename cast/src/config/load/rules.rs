@@ -1,8 +1,11 @@
-use crate::Config;
+use crate::{get_placeholders, Config, Expr, Pipeline, Step, StepOrParallel};
 
 // Error Handling
 use log::warn;
 use miette::Result;
+use pipelight_error::{CastError, RuleError};
+use regex::Regex;
+use std::collections::HashMap;
 
 impl Config {
     /**
@@ -21,4 +24,274 @@ impl Config {
         }
         Ok(self.to_owned())
     }
+    /**
+    Raises an error pointing into the config file source.
+
+    Enforces step rules:
+    - Steps can only need steps declared in the same pipeline.
+    - Steps can't need steps of their own parallel block.
+    - Step dependencies can't be circular.
+    - Matrix variables must have at least one value.
+    - Step shells must name a program.
+    - Step conditions must be valid expressions using known variables.
+    - Command placeholders must use known variables.
+    - Step outputs must be captured from the "last_line" or a valid regex.
+    */
+    pub fn check_rules(&self, src: &str) -> Result<()> {
+        if let Some(pipelines) = &self.pipelines {
+            for pipeline in pipelines {
+                pipeline.check_needs(src)?;
+                pipeline.check_matrix(src)?;
+                pipeline.check_shell(src)?;
+                pipeline.check_conditions(src)?;
+                pipeline.check_placeholders(src)?;
+                pipeline.check_outputs(src)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Pipeline {
+    fn get_steps(&self) -> Vec<&Step> {
+        let mut steps = vec![];
+        for step in &self.steps {
+            match step {
+                StepOrParallel::Step(res) => steps.push(res),
+                StepOrParallel::Parallel(res) => steps.extend(res.parallel.iter()),
+            }
+        }
+        steps
+    }
+    fn check_conditions(&self, src: &str) -> Result<()> {
+        let steps = self.get_steps();
+        let names: Vec<&str> = steps.iter().map(|e| e.name.as_str()).collect();
+        for step in &steps {
+            if let Some(when) = &step.when {
+                let keywords = [self.name.as_str(), step.name.as_str(), when.as_str()];
+                let expr = match Expr::parse(when) {
+                    Ok(res) => res,
+                    Err(e) => {
+                        let err = RuleError::new(&e.message, &e.help, src, &keywords);
+                        return Err(CastError::RuleError(err).into());
+                    }
+                };
+                for variable in expr.get_variables() {
+                    if !is_known_variable(&variable, &names) {
+                        let message = format!(
+                            "The step \"{}\" condition uses an unknown variable \"{}\"",
+                            step.name, variable
+                        );
+                        let help = KNOWN_VARIABLES;
+                        let err = RuleError::new(&message, help, src, &keywords);
+                        return Err(CastError::RuleError(err).into());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    fn check_outputs(&self, src: &str) -> Result<()> {
+        for step in self.get_steps() {
+            for (name, source) in step.outputs.iter().flatten() {
+                if source == "last_line" {
+                    continue;
+                }
+                if let Err(e) = Regex::new(source) {
+                    let message = format!(
+                        "The step \"{}\" output \"{}\" is not a valid regex",
+                        step.name, name
+                    );
+                    let help = format!(
+                        "Use \"last_line\" or a valid regex, with an optional capture group\n{}",
+                        e
+                    );
+                    let keywords = [self.name.as_str(), step.name.as_str(), "outputs", name];
+                    let err = RuleError::new(&message, &help, src, &keywords);
+                    return Err(CastError::RuleError(err).into());
+                }
+            }
+        }
+        Ok(())
+    }
+    fn check_placeholders(&self, src: &str) -> Result<()> {
+        let steps = self.get_steps();
+        let names: Vec<&str> = steps.iter().map(|e| e.name.as_str()).collect();
+        for step in &steps {
+            for command in &step.commands {
+                for variable in get_placeholders(command) {
+                    let is_matrix = match (variable.strip_prefix("matrix."), &step.matrix) {
+                        (Some(key), Some(matrix)) => matrix.contains_key(key),
+                        _ => false,
+                    };
+                    if !is_matrix && !is_known_variable(&variable, &names) {
+                        let message = format!(
+                            "The step \"{}\" command uses an unknown variable \"{}\"",
+                            step.name, variable
+                        );
+                        let keywords = [
+                            self.name.as_str(),
+                            step.name.as_str(),
+                            command.as_str(),
+                            variable.as_str(),
+                        ];
+                        let err = RuleError::new(&message, KNOWN_VARIABLES, src, &keywords);
+                        return Err(CastError::RuleError(err).into());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    fn check_matrix(&self, src: &str) -> Result<()> {
+        for step in self.get_steps() {
+            for (key, values) in step.matrix.iter().flatten() {
+                if values.is_empty() {
+                    let message = format!(
+                        "The step \"{}\" matrix variable \"{}\" has no values",
+                        step.name, key
+                    );
+                    let help = "Add some values or remove the variable from the matrix";
+                    let keywords = [self.name.as_str(), step.name.as_str(), "matrix", key];
+                    let err = CastError::RuleError(RuleError::new(&message, help, src, &keywords));
+                    return Err(err.into());
+                }
+            }
+        }
+        Ok(())
+    }
+    fn check_shell(&self, src: &str) -> Result<()> {
+        for step in self.get_steps() {
+            if step.shell.as_ref().is_some_and(|e| e.is_empty()) {
+                let message = format!("The step \"{}\" shell is empty", step.name);
+                let help = "Provide a program and its arguments, ex: [\"bash\", \"-c\"]";
+                let keywords = [self.name.as_str(), step.name.as_str(), "shell"];
+                let err = CastError::RuleError(RuleError::new(&message, help, src, &keywords));
+                return Err(err.into());
+            }
+        }
+        Ok(())
+    }
+    fn check_needs(&self, src: &str) -> Result<()> {
+        let steps = self.get_steps();
+        let graph: HashMap<&str, Vec<&str>> = steps
+            .iter()
+            .map(|e| {
+                let needs = e.needs.iter().flatten().map(|e| e.as_str()).collect();
+                (e.name.as_str(), needs)
+            })
+            .collect();
+
+        // Unknown dependencies
+        for step in &steps {
+            for need in step.needs.iter().flatten() {
+                if !graph.contains_key(need.as_str()) {
+                    let message = format!(
+                        "The step \"{}\" needs an unknown step \"{}\"",
+                        step.name, need
+                    );
+                    let help = format!(
+                        "Declare a step named \"{}\" in the pipeline \"{}\" or remove it from the needs list",
+                        need, self.name
+                    );
+                    let keywords = [self.name.as_str(), step.name.as_str(), "needs", need];
+                    let err = CastError::RuleError(RuleError::new(&message, &help, src, &keywords));
+                    return Err(err.into());
+                }
+            }
+        }
+
+        // Dependencies inside a parallel block, which are run together
+        for step in &self.steps {
+            if let StepOrParallel::Parallel(res) = step {
+                let names: Vec<&str> = res.parallel.iter().map(|e| e.name.as_str()).collect();
+                for step in &res.parallel {
+                    for need in step.needs.iter().flatten() {
+                        if names.contains(&need.as_str()) {
+                            let message = format!(
+                                "The step \"{}\" needs the step \"{}\" of the same parallel block",
+                                step.name, need
+                            );
+                            let help = "Steps of a parallel block are run together, move one of them out of the block";
+                            let keywords = [self.name.as_str(), step.name.as_str(), "needs", need];
+                            let err = CastError::RuleError(RuleError::new(
+                                &message, help, src, &keywords,
+                            ));
+                            return Err(err.into());
+                        }
+                    }
+                }
+            }
+        }
+
+        // Circular dependencies
+        let mut done: Vec<&str> = vec![];
+        for step in &steps {
+            let mut path: Vec<&str> = vec![];
+            if let Some(cycle) = find_cycle(&graph, &step.name, &mut path, &mut done) {
+                let message = format!(
+                    "The pipeline \"{}\" has a step dependency cycle: {}",
+                    self.name,
+                    cycle.join(" -> ")
+                );
+                let help = "Remove one of those steps from the needs list to break the cycle";
+                let keywords = [self.name.as_str(), cycle[0], "needs"];
+                let err = CastError::RuleError(RuleError::new(&message, help, src, &keywords));
+                return Err(err.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+Depth first search for a dependency cycle starting from the given step.
+Returns the steps composing the cycle (first and last are the same).
+*/
+fn find_cycle<'a>(
+    graph: &HashMap<&'a str, Vec<&'a str>>,
+    name: &'a str,
+    path: &mut Vec<&'a str>,
+    done: &mut Vec<&'a str>,
+) -> Option<Vec<&'a str>> {
+    if done.contains(&name) {
+        return None;
+    }
+    if let Some(index) = path.iter().position(|e| *e == name) {
+        let mut cycle = path[index..].to_vec();
+        cycle.push(name);
+        return Some(cycle);
+    }
+    path.push(name);
+    for need in graph.get(name).into_iter().flatten() {
+        if let Some(cycle) = find_cycle(graph, need, path, done) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    done.push(name);
+    None
+}
+
+const KNOWN_VARIABLES: &str = "Known variables are: git.branch, git.tag, git.commit, git.action (or trigger.<same>), pipeline.name, env.<NAME>, steps.<name>.status and steps.<name>.outputs.<key>";
+
+/**
+Whether the variable path can be resolved at runtime.
+*/
+fn is_known_variable(path: &str, steps: &[&str]) -> bool {
+    let parts: Vec<&str> = path.splitn(2, '.').collect();
+    match parts[..] {
+        ["git" | "trigger", "branch" | "tag" | "commit" | "action"] => true,
+        ["pipeline", "name"] => true,
+        ["env", name] => !name.is_empty(),
+        ["steps", rest] => match rest.rsplit_once('.') {
+            Some((name, "status")) => steps.contains(&name),
+            Some((name, key)) => match name.strip_suffix(".outputs") {
+                Some(name) => steps.contains(&name) && !key.is_empty(),
+                None => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    }
 }
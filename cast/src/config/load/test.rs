@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use crate::{Pipeline, Trigger, TriggerBranch, TriggerTag};
-    use miette::Result;
+    use crate::{Config, Pipeline, StepOrParallel, Trigger, TriggerBranch, TriggerTag};
+    use miette::{IntoDiagnostic, Result};
 
     #[test]
     fn normal() {
@@ -121,4 +121,293 @@ mod tests {
         assert!(res.is_err());
         Ok(())
     }
+    #[test]
+    fn pipeline_env() -> Result<()> {
+        let toml = r#"
+        name = "test"
+        env = { FOO = "pipeline" }
+        [[steps]]
+        name = "my_step"
+        commands = ["echo $FOO"]
+        env = { FOO = "step" }
+        "#;
+        let res = toml::from_str::<Pipeline>(toml);
+        assert!(res.is_ok());
+        Ok(())
+    }
+    #[test]
+    fn step_retry() -> Result<()> {
+        let toml = r#"
+        name = "test"
+        [[steps]]
+        name = "my_step"
+        commands = ["curl https://example.com"]
+        options = { retry = { attempts = 3, backoff = "PT1S", exit_codes = [6, 7] } }
+        "#;
+        let res = toml::from_str::<Pipeline>(toml);
+        assert!(res.is_ok());
+        Ok(())
+    }
+    #[test]
+    fn step_allowed_exit_codes() -> Result<()> {
+        let toml = r#"
+        name = "test"
+        [[steps]]
+        name = "lint"
+        commands = ["cargo clippy"]
+        options = { allowed_exit_codes = [0, 3] }
+        "#;
+        let res = toml::from_str::<Pipeline>(toml);
+        assert!(res.is_ok());
+        Ok(())
+    }
+    #[test]
+    fn step_limits() -> Result<()> {
+        let toml = r#"
+        name = "test"
+        [[steps]]
+        name = "test"
+        commands = ["cargo test"]
+        options = { limits = { memory = "2G", cpu = 600, open_files = 1024, file_size = "100M" } }
+        "#;
+        let res = toml::from_str::<Pipeline>(toml);
+        assert!(res.is_ok());
+        Ok(())
+    }
+    #[test]
+    fn step_tty() -> Result<()> {
+        let toml = r#"
+        name = "test"
+        [[steps]]
+        name = "test"
+        commands = ["cargo test"]
+        options = { tty = true }
+        "#;
+        let res = toml::from_str::<Pipeline>(toml);
+        assert!(res.is_ok());
+        Ok(())
+    }
+    #[test]
+    fn step_workdir_and_shell() -> Result<()> {
+        let toml = r#"
+        [[pipelines]]
+        name = "test"
+        [[pipelines.steps]]
+        name = "build"
+        commands = ["npm run build"]
+        workdir = "packages/foo"
+        shell = ["bash", "-euo", "pipefail", "-c"]
+        "#;
+        let config = toml::from_str::<Config>(toml).unwrap();
+        assert!(config.check_rules(toml).is_ok());
+
+        let toml = toml.replace(r#"["bash", "-euo", "pipefail", "-c"]"#, "[]");
+        let config = toml::from_str::<Config>(&toml).unwrap();
+        assert!(config.check_rules(&toml).is_err());
+        Ok(())
+    }
+    #[test]
+    fn logs_retention() -> Result<()> {
+        let toml = r#"
+        [options]
+        logs = { keep = 20, max_age = "P30D", max_size = "500M" }
+        [[pipelines]]
+        name = "test"
+        [[pipelines.steps]]
+        name = "build"
+        commands = ["npm run build"]
+        "#;
+        let res = toml::from_str::<Config>(toml);
+        assert!(res.is_ok());
+        Ok(())
+    }
+    #[test]
+    fn step_needs() -> Result<()> {
+        let toml = r#"
+        [[pipelines]]
+        name = "test"
+        [[pipelines.steps]]
+        name = "build"
+        commands = ["cargo build"]
+        [[pipelines.steps]]
+        name = "test"
+        commands = ["cargo test"]
+        needs = ["build"]
+        "#;
+        let config = toml::from_str::<Config>(toml).unwrap();
+        assert!(config.check_rules(toml).is_ok());
+        Ok(())
+    }
+    #[test]
+    fn step_needs_unknown() -> Result<()> {
+        let toml = r#"
+        [[pipelines]]
+        name = "test"
+        [[pipelines.steps]]
+        name = "test"
+        commands = ["cargo test"]
+        needs = ["build"]
+        "#;
+        let config = toml::from_str::<Config>(toml).unwrap();
+        assert!(config.check_rules(toml).is_err());
+        Ok(())
+    }
+    #[test]
+    fn step_needs_same_parallel() -> Result<()> {
+        let toml = r#"
+        [[pipelines]]
+        name = "test"
+        [[pipelines.steps]]
+        [[pipelines.steps.parallel]]
+        name = "build"
+        commands = ["cargo build"]
+        [[pipelines.steps.parallel]]
+        name = "test"
+        commands = ["cargo test"]
+        needs = ["build"]
+        "#;
+        let config = toml::from_str::<Config>(toml).unwrap();
+        let err = config.check_rules(toml).unwrap_err();
+        assert!(err.to_string().contains("of the same parallel block"));
+        Ok(())
+    }
+    #[test]
+    fn step_needs_cycle() -> Result<()> {
+        let toml = r#"
+        [[pipelines]]
+        name = "test"
+        [[pipelines.steps]]
+        name = "build"
+        commands = ["cargo build"]
+        needs = ["lint"]
+        [[pipelines.steps]]
+        name = "lint"
+        commands = ["cargo clippy"]
+        needs = ["build"]
+        "#;
+        let config = toml::from_str::<Config>(toml).unwrap();
+        let err = config.check_rules(toml).unwrap_err();
+        assert!(err.to_string().contains("build -> lint -> build"));
+        Ok(())
+    }
+    #[test]
+    fn step_matrix() -> Result<()> {
+        let yaml = r#"
+        pipelines:
+          - name: test
+            steps:
+              - name: build ${{ matrix.target }}
+                commands:
+                  - cargo build --target ${{ matrix.target }}
+                matrix:
+                  target: [x86_64, aarch64]
+                  profile: []
+        "#;
+        let config = serde_yaml::from_str::<Config>(yaml).unwrap();
+        assert!(config.check_rules(yaml).is_err());
+        Ok(())
+    }
+    #[test]
+    fn step_matrix_scalars() -> Result<()> {
+        let yaml = r#"
+        pipelines:
+          - name: test
+            steps:
+              - name: test ${{ matrix.node }}
+                commands:
+                  - npm test
+                matrix:
+                  node: [18, 20]
+                  python: [3.12]
+                  coverage: [true, false]
+        "#;
+        let config = serde_yaml::from_str::<Config>(yaml).into_diagnostic()?;
+        let step = match &config.pipelines.unwrap()[0].steps[0] {
+            StepOrParallel::Step(step) => step.to_owned(),
+            _ => unreachable!(),
+        };
+        let matrix = step.matrix.unwrap();
+        assert_eq!(matrix["node"], vec!["18", "20"]);
+        assert_eq!(matrix["python"], vec!["3.12"]);
+        assert_eq!(matrix["coverage"], vec!["true", "false"]);
+
+        let toml = r#"
+        [[pipelines]]
+        name = "test"
+        [[pipelines.steps]]
+        name = "test"
+        commands = ["npm test"]
+        matrix = { node = [18, "lts"] }
+        "#;
+        let config = toml::from_str::<Config>(toml).into_diagnostic()?;
+        assert!(config.check_rules(toml).is_ok());
+        Ok(())
+    }
+    #[test]
+    fn step_condition() -> Result<()> {
+        let toml = r#"
+        [[pipelines]]
+        name = "test"
+        [[pipelines.steps]]
+        name = "build"
+        commands = ["cargo build"]
+        [[pipelines.steps]]
+        name = "deploy"
+        commands = ["./deploy.sh"]
+        if = "trigger.branch == 'main' && steps.build.status == 'succeeded'"
+        "#;
+        let config = toml::from_str::<Config>(toml).unwrap();
+        assert!(config.check_rules(toml).is_ok());
+
+        // Unknown variable
+        let toml = toml.replace("steps.build.status", "steps.lint.status");
+        let config = toml::from_str::<Config>(&toml).unwrap();
+        assert!(config.check_rules(&toml).is_err());
+        Ok(())
+    }
+    #[test]
+    fn command_placeholders() -> Result<()> {
+        let yaml = r#"
+        pipelines:
+          - name: test
+            steps:
+              - name: build
+                commands:
+                  - echo ${{ git.commit }} ${{ pipeline.name }} ${{ env.HOME }}
+              - name: notify
+                commands:
+                  - echo ${{ steps.build.status }}
+        "#;
+        let config = serde_yaml::from_str::<Config>(yaml).unwrap();
+        assert!(config.check_rules(yaml).is_ok());
+
+        // Unknown variable
+        let yaml = yaml.replace("git.commit", "git.sha");
+        let config = serde_yaml::from_str::<Config>(&yaml).unwrap();
+        let err = config.check_rules(&yaml).unwrap_err();
+        assert!(err.to_string().contains("git.sha"));
+        Ok(())
+    }
+    #[test]
+    fn step_outputs() -> Result<()> {
+        let toml = r#"
+        [[pipelines]]
+        name = "test"
+        [[pipelines.steps]]
+        name = "build"
+        commands = ["cargo build"]
+        outputs = { last = "last_line", version = "version: (\\S+)" }
+        [[pipelines.steps]]
+        name = "release"
+        commands = ["echo ${{ steps.build.outputs.version }}"]
+        "#;
+        let config = toml::from_str::<Config>(toml).unwrap();
+        assert!(config.check_rules(toml).is_ok());
+
+        // Invalid regex
+        let toml = toml.replace(r"(\\S+)", r"(\\S+");
+        let config = toml::from_str::<Config>(&toml).unwrap();
+        assert!(config.check_rules(&toml).is_err());
+        Ok(())
+    }
 }
@@ -2,7 +2,8 @@
 // Serde is the rust crate to that implements the logic
 // to parse structs from filepaths.
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 /**
 Options to tweak global pipelines behavior
@@ -14,6 +15,22 @@ pub struct ConfigOpts {
     // when triggered by a git hook.
     pub attach: Option<bool>,
     pub log_level: Option<String>,
+    // The pipelines logs retention policy.
+    pub logs: Option<LogsOpts>,
+}
+
+/**
+Logs retention policy, applied after every pipeline run.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LogsOpts {
+    // The number of runs to keep per pipeline.
+    pub keep: Option<usize>,
+    // The maximum age of a run as an ISO8601 duration (ex: "P30D").
+    pub max_age: Option<String>,
+    // The maximum total size of the logs (ex: "500M").
+    pub max_size: Option<String>,
 }
 
 /**
@@ -44,6 +61,10 @@ pub struct PipelineOpts {
     // when triggered by a git hook.
     pub attach: Option<bool>,
     pub log_level: Option<String>,
+    // The maximum pipeline execution time as an ISO8601 duration (ex: "PT10M").
+    pub timeout: Option<String>,
+    // The maximum size of a command stdout and stderr kept in the logs (ex: "1M").
+    pub max_output: Option<String>,
 }
 
 /**
@@ -55,6 +76,8 @@ pub struct Pipeline {
     pub name: String,
     pub triggers: Option<Vec<Trigger>>,
     pub steps: Vec<StepOrParallel>,
+    // Environment variables passed to every step
+    pub env: Option<HashMap<String, String>>,
     #[serde(flatten)]
     pub fallback: Option<Fallback>,
     pub options: Option<PipelineOpts>,
@@ -67,6 +90,48 @@ Options to tweak step behavior and command execution
 pub struct StepOpts {
     // The step's command execution behavior
     pub mode: Option<String>,
+    // The maximum step execution time as an ISO8601 duration (ex: "PT30S").
+    pub timeout: Option<String>,
+    // Retry failing commands
+    pub retry: Option<Retry>,
+    // Exit codes that are considered successful (ex: [0, 3]).
+    pub allowed_exit_codes: Option<Vec<i32>>,
+    // Resources the step commands can consume
+    pub limits: Option<Limits>,
+    // Run the commands in a pseudo-terminal, as if run interactively.
+    // Their stderr is then merged into the stdout, that outputs are captured from.
+    pub tty: Option<bool>,
+}
+
+/**
+Resource limits for the step commands and their children.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Limits {
+    // The maximum virtual memory size (ex: "512M", "2G").
+    pub memory: Option<String>,
+    // The maximum CPU time in seconds.
+    pub cpu: Option<u64>,
+    // The maximum number of open files.
+    pub open_files: Option<u64>,
+    // The maximum size of a written file (ex: "100M").
+    pub file_size: Option<String>,
+}
+
+/**
+Retry policy for flaky step commands.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Retry {
+    // The maximum number of attempts (including the first one).
+    pub attempts: u32,
+    // The delay before the first retry as an ISO8601 duration,
+    // doubled on every subsequent attempt.
+    pub backoff: Option<String>,
+    // Only retry when the command exits with one of these codes.
+    pub exit_codes: Option<Vec<i32>>,
 }
 
 /**
@@ -77,11 +142,66 @@ Steps are a named list of Commands.
 pub struct Step {
     pub name: String,
     pub commands: Vec<String>,
+    // Environment variables passed to every command
+    pub env: Option<HashMap<String, String>>,
+    // Names of the steps that must succeed before this one starts
+    pub needs: Option<Vec<String>>,
+    // Variables whose every combination of values makes a concrete step
+    #[serde(default, deserialize_with = "deserialize_matrix")]
+    pub matrix: Option<BTreeMap<String, Vec<String>>>,
+    // A condition expression, the step is skipped when false
+    #[serde(alias = "if")]
+    pub when: Option<String>,
+    // Named values taken from the step stdout,
+    // either the "last_line" or the first match of a regex (or of its first capture group).
+    pub outputs: Option<BTreeMap<String, String>>,
+    // The directory commands are run in
+    pub workdir: Option<String>,
+    // The shell program and its arguments commands are run with,
+    // ex: ["bash", "-euo", "pipefail", "-c"]
+    pub shell: Option<Vec<String>>,
     pub options: Option<StepOpts>,
     #[serde(flatten)]
     pub fallback: Option<Fallback>,
 }
 
+/**
+A matrix variable value.
+Numbers and booleans are accepted (ex: `node: [18, 20]`) and taken as strings.
+*/
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MatrixValue {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+}
+impl From<MatrixValue> for String {
+    fn from(e: MatrixValue) -> Self {
+        match e {
+            MatrixValue::String(e) => e,
+            MatrixValue::Integer(e) => e.to_string(),
+            MatrixValue::Float(e) => e.to_string(),
+            MatrixValue::Bool(e) => e.to_string(),
+        }
+    }
+}
+fn deserialize_matrix<'de, D>(
+    deserializer: D,
+) -> Result<Option<BTreeMap<String, Vec<String>>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let matrix = Option::<BTreeMap<String, Vec<MatrixValue>>>::deserialize(deserializer)?;
+    Ok(matrix.map(|matrix| {
+        matrix
+            .into_iter()
+            .map(|(key, values)| (key, values.into_iter().map(String::from).collect()))
+            .collect()
+    }))
+}
+
 /**
 Parallel are unnamed list of steps.
 */
@@ -91,6 +211,8 @@ pub struct Parallel {
     pub parallel: Vec<Step>,
     // pub options: Option<StepOpts>,
     pub mode: Option<String>,
+    // Environment variables passed to every parallel step
+    pub env: Option<HashMap<String, String>>,
     #[serde(flatten)]
     pub fallback: Option<Fallback>,
 }
@@ -102,6 +224,7 @@ As a  developer, I find it a bit of an overhead to use.
 But it is the simplest way I have found to make
 a usable **Union** (Step must be This type OR This type).
 */
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 #[serde(untagged)]
 #[serde(deny_unknown_fields)]
@@ -0,0 +1,232 @@
+// Error Handling
+use pipelight_error::LibError;
+
+mod placeholder;
+// Tests
+mod test;
+
+pub use placeholder::{get_placeholders, interpolate, Quoting};
+
+/**
+A tiny expression language to write step conditions.
+
+Expressions compare variables against string literals
+and combine the results with boolean operators.
+
+```txt
+trigger.action == "pre-push" && !(trigger.branch == "main")
+steps.build.status != "failed" || env.FORCE == "true"
+```
+
+Supported syntax:
+- string literals: `"double"` or `'single'` quoted,
+- the `true`, `false` and `null` keywords,
+- dotted variable paths: `trigger.branch`, `env.HOME`, `steps.build.status`...
+- operators by ascending precedence: `||`, `&&`, `==` `!=`, `!`, and parenthesis.
+*/
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Expr {
+    Str(String),
+    Bool(bool),
+    Null,
+    Var(String),
+    Not(Box<Expr>),
+    Eq(Box<Expr>, Box<Expr>),
+    Ne(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Token {
+    Str(String),
+    Ident(String),
+    LParen,
+    RParen,
+    Not,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl Expr {
+    /**
+    Parse an expression string into an expression tree.
+    */
+    pub fn parse(text: &str) -> Result<Expr, LibError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            text: text.to_owned(),
+            tokens,
+            index: 0,
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(syntax_error(text, &format!("unexpected token {:?}", token)));
+        }
+        Ok(expr)
+    }
+    /**
+    Return every variable path used in the expression.
+    */
+    pub fn get_variables(&self) -> Vec<String> {
+        match self {
+            Expr::Var(path) => vec![path.to_owned()],
+            Expr::Not(e) => e.get_variables(),
+            Expr::Eq(a, b) | Expr::Ne(a, b) | Expr::And(a, b) | Expr::Or(a, b) => {
+                let mut vars = a.get_variables();
+                vars.extend(b.get_variables());
+                vars
+            }
+            Expr::Str(_) | Expr::Bool(_) | Expr::Null => vec![],
+        }
+    }
+}
+
+fn syntax_error(text: &str, reason: &str) -> LibError {
+    LibError {
+        message: format!("Invalid expression \"{}\": {}", text, reason),
+        help: "Expressions look like: trigger.branch == \"main\" && env.CI != \"true\"".to_owned(),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, LibError> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' | '\r' => {}
+            '(' => tokens.push(Token::LParen),
+            ')' => tokens.push(Token::RParen),
+            '!' => {
+                if chars.next_if_eq(&'=').is_some() {
+                    tokens.push(Token::Ne);
+                } else {
+                    tokens.push(Token::Not);
+                }
+            }
+            '=' => {
+                if chars.next_if_eq(&'=').is_none() {
+                    return Err(syntax_error(text, "expected \"==\""));
+                }
+                tokens.push(Token::Eq);
+            }
+            '&' => {
+                if chars.next_if_eq(&'&').is_none() {
+                    return Err(syntax_error(text, "expected \"&&\""));
+                }
+                tokens.push(Token::And);
+            }
+            '|' => {
+                if chars.next_if_eq(&'|').is_none() {
+                    return Err(syntax_error(text, "expected \"||\""));
+                }
+                tokens.push(Token::Or);
+            }
+            '"' | '\'' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some(e) if e == c => break,
+                        Some(e) => string.push(e),
+                        None => return Err(syntax_error(text, "unterminated string")),
+                    }
+                }
+                tokens.push(Token::Str(string));
+            }
+            c if is_ident_char(c) => {
+                let mut ident = c.to_string();
+                while let Some(e) = chars.next_if(|e| is_ident_char(*e)) {
+                    ident.push(e);
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            c => return Err(syntax_error(text, &format!("unexpected character '{}'", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/**
+A recursive descent parser, one method per precedence level.
+*/
+struct Parser {
+    text: String,
+    tokens: Vec<Token>,
+    index: usize,
+}
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+    fn error(&self, reason: &str) -> LibError {
+        syntax_error(&self.text, reason)
+    }
+    fn or(&mut self) -> Result<Expr, LibError> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+    fn and(&mut self) -> Result<Expr, LibError> {
+        let mut expr = self.comparison()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.comparison()?));
+        }
+        Ok(expr)
+    }
+    fn comparison(&mut self) -> Result<Expr, LibError> {
+        let expr = self.unary()?;
+        match self.peek() {
+            Some(Token::Eq) => {
+                self.next();
+                Ok(Expr::Eq(Box::new(expr), Box::new(self.unary()?)))
+            }
+            Some(Token::Ne) => {
+                self.next();
+                Ok(Expr::Ne(Box::new(expr), Box::new(self.unary()?)))
+            }
+            _ => Ok(expr),
+        }
+    }
+    fn unary(&mut self) -> Result<Expr, LibError> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+    fn primary(&mut self) -> Result<Expr, LibError> {
+        match self.next() {
+            Some(Token::LParen) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(self.error("missing closing parenthesis")),
+                }
+            }
+            Some(Token::Str(string)) => Ok(Expr::Str(string)),
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "true" => Ok(Expr::Bool(true)),
+                "false" => Ok(Expr::Bool(false)),
+                "null" => Ok(Expr::Null),
+                _ => Ok(Expr::Var(ident)),
+            },
+            Some(token) => Err(self.error(&format!("unexpected token {:?}", token))),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+// Interpolation
+use regex::Regex;
+
+fn get_regex() -> Regex {
+    Regex::new(r"\$\{\{\s*([^}]*?)\s*\}\}").unwrap()
+}
+
+/**
+Return the variable paths of the `${{ <path> }}` placeholders found in the text.
+*/
+pub fn get_placeholders(text: &str) -> Vec<String> {
+    get_regex()
+        .captures_iter(text)
+        .map(|caps| caps[1].to_owned())
+        .collect()
+}
+
+/**
+The shell quotes a placeholder is written in.
+*/
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Quoting {
+    None,
+    Single,
+    Double,
+}
+impl Quoting {
+    /**
+    Return the quotes left open once the shell text is read from this state.
+    */
+    fn after(self, text: &str) -> Quoting {
+        let mut quoting = self;
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            quoting = match (quoting, c) {
+                (Quoting::Single, '\'') => Quoting::None,
+                (Quoting::Single, _) => Quoting::Single,
+                (_, '\\') => {
+                    chars.next();
+                    quoting
+                }
+                (Quoting::None, '\'') => Quoting::Single,
+                (Quoting::None, '"') => Quoting::Double,
+                (Quoting::Double, '"') => Quoting::None,
+                (quoting, _) => quoting,
+            };
+        }
+        quoting
+    }
+}
+
+/**
+Replace the `${{ <path> }}` placeholders with the values returned by the resolver,
+which is given the shell quotes every placeholder is written in.
+Unresolved variables are replaced with an empty string.
+*/
+pub fn interpolate<F>(text: &str, resolve: F) -> String
+where
+    F: Fn(&str, Quoting) -> Option<String>,
+{
+    let mut res = String::new();
+    let mut quoting = Quoting::None;
+    let mut last = 0;
+    for caps in get_regex().captures_iter(text) {
+        let placeholder = caps.get(0).unwrap();
+        let before = &text[last..placeholder.start()];
+        quoting = quoting.after(before);
+        res.push_str(before);
+        res.push_str(&resolve(&caps[1], quoting).unwrap_or_default());
+        last = placeholder.end();
+    }
+    res.push_str(&text[last..]);
+    res
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod expression {
+    use crate::{get_placeholders, interpolate, Expr};
+
+    #[test]
+    fn parse_comparison() {
+        let expr = Expr::parse("trigger.branch == 'main'").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Eq(
+                Box::new(Expr::Var("trigger.branch".to_owned())),
+                Box::new(Expr::Str("main".to_owned()))
+            )
+        );
+    }
+    #[test]
+    fn parse_precedence() {
+        let expr = Expr::parse("!true || false && env.CI != \"true\"").unwrap();
+        assert!(matches!(expr, Expr::Or(_, _)));
+        assert_eq!(expr.get_variables(), vec!["env.CI".to_owned()]);
+    }
+    #[test]
+    fn parse_errors() {
+        assert!(Expr::parse("trigger.branch = 'main'").is_err());
+        assert!(Expr::parse("(true").is_err());
+        assert!(Expr::parse("'main").is_err());
+        assert!(Expr::parse("true false").is_err());
+        assert!(Expr::parse("").is_err());
+    }
+    #[test]
+    fn placeholders() {
+        let text = "git checkout ${{ git.commit }} && echo ${{pipeline.name}}";
+        assert_eq!(get_placeholders(text), vec!["git.commit", "pipeline.name"]);
+        let res = interpolate(text, |path, _| match path {
+            "git.commit" => Some("abc123".to_owned()),
+            _ => None,
+        });
+        assert_eq!(res, "git checkout abc123 && echo ");
+    }
+    #[test]
+    fn placeholders_quoting() {
+        let text = r#"echo ${{ a }} "${{ a }} '${{ a }}'" '${{ a }} \"' \"${{ a }}"#;
+        let res = interpolate(text, |_, quoting| Some(format!("{:?}", quoting)));
+        assert_eq!(res, r#"echo None "Double 'Double'" 'Single \"' \"None"#);
+    }
+}
@@ -3,9 +3,11 @@
 //! Files are converted into intermediate rust structs by the well-known rust serde crate.
 //!
 mod config;
+mod expression;
 mod logs;
 
 // Re-export
 pub use config::types::*;
+pub use expression::{get_placeholders, interpolate, Expr, Quoting};
 pub use logs::Logs;
 pub use logs::*;
@@ -30,7 +30,9 @@ impl Logs {
         let entries = fs::read_dir(directory_path).into_diagnostic()?;
         for entry in entries {
             let entry = entry.into_diagnostic()?;
-            if entry.file_type().into_diagnostic()?.is_file() {
+            // Skip the index and temporary files
+            let is_json = entry.path().extension().and_then(|e| e.to_str()) == Some("json");
+            if entry.file_type().into_diagnostic()?.is_file() && is_json {
                 let res = read_last_line(&entry.path());
                 match res {
                     Ok(json) => {
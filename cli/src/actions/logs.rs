@@ -1,18 +1,85 @@
 // Types
+use crate::types::LogsFilters;
 use pipelight_exec::{Statuable, Status};
-use workflow::{pipeline::Filters, Getters, Logs, Node, Pipeline};
+use pipelight_utils::git::Flag;
+use workflow::pipeline::{Filters, Query};
+use workflow::traits::display::Junit;
+use workflow::{Config, Getters, Logs, Node, Pipeline, StepOrParallel};
+// Parse
+use convert_case::{Case, Casing};
+// Colorize
+use colored::Colorize;
+// Filesystem
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::{thread, time};
 // Error Handling
-use miette::{IntoDiagnostic, Result};
+use miette::{miette, Error, IntoDiagnostic, Result};
 
 /**
-Pretty print pipelines as a tree
+Delay between two reads of a followed pipeline log.
 */
-pub fn pretty(name: Option<String>) -> Result<()> {
-    let mut pipelines = Logs::get()?;
+const POLLING_INTERVAL: time::Duration = time::Duration::from_millis(200);
 
-    if let Some(name) = name {
-        pipelines = Filters::filter_by_name(pipelines, &name)?;
+/**
+A followed command output file,
+kept open to only read the bytes appended since the last read.
+*/
+struct FollowedFile {
+    file: File,
+    // The read bytes of a line still being written
+    pending: Vec<u8>,
+}
+
+/**
+Build a logs query from the command line filters.
+*/
+pub fn get_query(name: Option<String>, filters: &LogsFilters) -> Result<Query> {
+    let mut query = Query {
+        name,
+        branch: filters.branch.clone(),
+        tag: filters.tag.clone(),
+        commit: filters.commit.clone(),
+        limit: filters.limit,
+        ..Default::default()
+    };
+    if let Some(status) = &filters.status {
+        match serde_plain::from_str::<Status>(&status.to_case(Case::Kebab)) {
+            Ok(status) => query.status = Some(status),
+            Err(_) => {
+                let message = format!("The status {} is not known", status);
+                let help =
+                    "Use one of: started, running, succeeded, failed, aborted, timed-out, skipped";
+                return Err(miette!(help = help, "{}", message));
+            }
+        }
+    }
+    if let Some(action) = &filters.action {
+        match serde_plain::from_str::<Flag>(&action.to_case(Case::Kebab)) {
+            Ok(action) => query.action = Some(action),
+            Err(_) => {
+                let message = format!("The action {} is not known", action);
+                let help = "Use a git hook name or a special flag (ex: pre-push, manual)";
+                return Err(miette!(help = help, "{}", message));
+            }
+        }
     }
+    if let Some(since) = &filters.since {
+        query.since = Some(Filters::parse_date(since)?);
+    }
+    if let Some(until) = &filters.until {
+        query.until = Some(Filters::parse_end_date(until)?);
+    }
+    Ok(query)
+}
+
+/**
+Pretty print pipelines as a tree
+*/
+pub fn pretty(query: &Query) -> Result<()> {
+    let pipelines = Logs::query(query)?;
 
     for mut pipeline in pipelines {
         if pipeline.get_status() == Some(Status::Running) {
@@ -27,11 +94,8 @@ pub fn pretty(name: Option<String>) -> Result<()> {
 /**
 Pretty print pipelines as json
 */
-pub fn json(name: Option<String>) -> Result<()> {
-    let mut pipelines = Logs::get()?;
-    if let Some(name) = name {
-        pipelines = Filters::filter_by_name(pipelines, &name)?;
-    }
+pub fn json(query: &Query) -> Result<()> {
+    let pipelines = Logs::query(query)?;
     for pipeline in pipelines {
         let pipeline_json =
             serde_json::to_string_pretty::<Pipeline>(&pipeline).into_diagnostic()?;
@@ -40,6 +104,110 @@ pub fn json(name: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/**
+Print pipelines as a JUnit XML report
+*/
+pub fn junit(query: &Query) -> Result<()> {
+    let pipelines = Logs::query(query)?;
+    print!("{}", Junit::from(&pipelines));
+    Ok(())
+}
+
+/**
+Follow the last run of a pipeline until it ends.
+
+Commands output lines are printed as soon as they are written,
+and the pipeline tree is printed again on every status change.
+*/
+pub fn follow(name: Option<String>) -> Result<()> {
+    let mut pipeline = match name {
+        Some(name) => Logs::get_by_name(&name)?,
+        None => match Logs::get_entries()?.pop() {
+            Some(entry) => Logs::get_by_uuid(&entry.uuid)?,
+            None => return Err(Error::msg("No logs to follow.")),
+        },
+    };
+    // The commands output files opened so far
+    let mut files: HashMap<String, FollowedFile> = HashMap::new();
+    // The last printed pipeline state
+    let mut last: Option<String> = None;
+    loop {
+        // A log line being written is read on the next iteration
+        if let Ok(res) = pipeline.read_log() {
+            pipeline = res;
+        }
+        let ended = match pipeline.get_status() {
+            Some(Status::Started) | Some(Status::Running) => {
+                if pipeline.is_running()? {
+                    false
+                } else {
+                    pipeline.set_status(Some(Status::Aborted));
+                    true
+                }
+            }
+            _ => true,
+        };
+
+        print_new_lines(&pipeline, &mut files)?;
+        let state = serde_json::to_string(&pipeline).into_diagnostic()?;
+        if last.as_ref() != Some(&state) {
+            println!("{}", Node::from(&pipeline));
+            last = Some(state);
+        }
+
+        if ended {
+            break;
+        }
+        thread::sleep(POLLING_INTERVAL);
+    }
+    Ok(())
+}
+
+/**
+Print the complete lines appended to the commands output files since the last read.
+*/
+fn print_new_lines(pipeline: &Pipeline, files: &mut HashMap<String, FollowedFile>) -> Result<()> {
+    let steps = pipeline.steps.iter().flat_map(|e| match e {
+        StepOrParallel::Step(res) => vec![res],
+        StepOrParallel::Parallel(res) => res.steps.iter().collect(),
+    });
+    for step in steps {
+        for command in &step.commands {
+            let (stdout_path, stderr_path) = command.process.io.get_paths();
+            for (path, is_stderr) in [(stdout_path, false), (stderr_path, true)] {
+                let followed = match files.entry(path) {
+                    Entry::Occupied(e) => e.into_mut(),
+                    Entry::Vacant(e) => match File::open(e.key()) {
+                        Ok(file) => e.insert(FollowedFile {
+                            file,
+                            pending: vec![],
+                        }),
+                        Err(_) => continue,
+                    },
+                };
+                followed
+                    .file
+                    .read_to_end(&mut followed.pending)
+                    .into_diagnostic()?;
+                let end = match followed.pending.iter().rposition(|e| *e == b'\n') {
+                    Some(i) => i + 1,
+                    None => continue,
+                };
+                let bytes: Vec<u8> = followed.pending.drain(..end).collect();
+                let text = String::from_utf8_lossy(&bytes);
+                for line in text.lines() {
+                    let line = format!("{} {}", format!("{} |", step.name).dimmed(), line);
+                    match is_stderr {
+                        true => eprintln!("{}", line),
+                        false => println!("{}", line),
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 /**
 Clean
 */
@@ -47,3 +215,37 @@ pub fn clean() -> Result<()> {
     Logs::clean()?;
     Ok(())
 }
+
+/**
+Delete the runs that fall out of the config file retention policy.
+On a dry run, only list them.
+*/
+pub fn prune(dry_run: bool) -> Result<()> {
+    let opts = Config::get()?.options.and_then(|e| e.logs);
+    let opts = match opts {
+        Some(opts) => opts,
+        None => {
+            let message = "No logs retention policy defined";
+            let help = "Add a \"logs\" option to the config file options (ex: { keep: 20 })";
+            return Err(miette!(help = help, "{}", message));
+        }
+    };
+    let pruned = Logs::prune(&opts, dry_run)?;
+    for entry in &pruned {
+        let date = match entry.get_date() {
+            Some(date) => date.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => "-".to_owned(),
+        };
+        println!(
+            "{} {} {}",
+            date,
+            entry.name,
+            entry.uuid.to_string().dimmed()
+        );
+    }
+    match dry_run {
+        true => println!("{} run(s) to prune", pruned.len()),
+        false => println!("{} run(s) pruned", pruned.len()),
+    }
+    Ok(())
+}
@@ -1,7 +1,9 @@
 // Test
 // Actions
 pub mod logs;
+pub mod retry;
 pub mod run;
+pub mod stats;
 pub mod stop;
 pub mod trigger;
 
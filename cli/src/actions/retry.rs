@@ -0,0 +1,87 @@
+// Struct
+use crate::services::{Action, FgBg, Service};
+use crate::types::{Attach, Commands, PostCommands};
+use pipelight_exec::Status;
+use uuid::Uuid;
+use workflow::{Config, FileStore, Getters, LogStore, Logs, Node, Pipeline};
+// Globals
+use crate::globals::CLI;
+use workflow::globals::STREAM;
+// Error Handling
+use miette::{Error, Result};
+
+/**
+Return the run to retry, designated by its uuid
+or by the pipeline name for its last run.
+*/
+pub fn get_parent(target: &str) -> Result<Pipeline> {
+    match Uuid::parse_str(target) {
+        Ok(uuid) => FileStore::default().load(&uuid).map_err(|_| {
+            let message = format!("Couldn't find a pipeline run with uuid {}, in logs", uuid);
+            Error::msg(message)
+        }),
+        Err(_) => Logs::get_by_name(target),
+    }
+}
+
+/**
+Retry a previous pipeline run.
+
+Steps that succeeded are reused and the run resumes
+from the first failed step, or from the provided one.
+*/
+pub fn launch() -> Result<()> {
+    let mut args = CLI.lock().unwrap().clone();
+
+    // Retrieve command line args
+    let (target, from) = match args.commands.clone() {
+        Commands::PostCommands(PostCommands::Retry(e)) => (e.target, e.from),
+        _ => {
+            let message = "Couldn't retrieve the run to retry";
+            return Err(Error::msg(message));
+        }
+    };
+    let parent = get_parent(&target)?;
+    let config = Config::get()?;
+
+    if args.attach.is_none() {
+        // Retrieve global options
+        if let Some(attach) = config.options.and_then(|e| e.attach) {
+            args.attach = Some(attach.to_string());
+        }
+        // Retrieve per-pipeline options
+        if let Some(attach) = parent.options.as_ref().and_then(|e| e.attach) {
+            args.attach = Some(attach.to_string());
+        }
+    }
+
+    // Action
+    match args.attach.clone() {
+        Some(val) if val == String::from(&Attach::True) => {
+            let mut pipeline = parent.resume(from.as_deref())?;
+            // Stream outputs to the terminal while running
+            *STREAM.lock().unwrap() = true;
+            pipeline.run()?;
+            // Return pipeline log
+            println!("{}", Node::from(&pipeline));
+
+            match pipeline.status {
+                Some(Status::Succeeded) => Ok(()),
+                Some(Status::Failed) => {
+                    let message = "Pipeline status: Failed";
+                    Err(Error::msg(message))
+                }
+                Some(Status::Aborted) => {
+                    let message = "Pipeline status: Aborted";
+                    Err(Error::msg(message))
+                }
+                Some(Status::TimedOut) => {
+                    let message = "Pipeline status: TimedOut";
+                    Err(Error::msg(message))
+                }
+                _ => Ok(()),
+            }
+        }
+        _ => Service::new(Action::Retry, Some(args))?.should_detach(),
+    }
+}
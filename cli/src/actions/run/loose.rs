@@ -1,6 +1,6 @@
 // Struct
 use crate::services::{Action, FgBg, Service};
-use crate::types::{Attach, Commands, DetachableCommands, PostCommands};
+use crate::types::{Attach, Commands, DetachableCommands, PostCommands, Selection};
 use pipelight_exec::Status;
 use workflow::{Config, Getters, Node, Pipeline};
 // Globals
@@ -8,6 +8,7 @@ use crate::globals::CLI;
 use crate::types::verbosity::{level_value, Verbosity};
 use log::LevelFilter;
 use pipelight_utils::globals::LOGGER;
+use workflow::globals::STREAM;
 // Traits
 // Error Handling
 use super::EXIT_CODE;
@@ -19,9 +20,11 @@ pub fn launch() -> Result<()> {
 
     // Retrieve command line args
     let name: String;
+    let selection: Selection;
     match args.commands.clone() {
         Commands::PostCommands(PostCommands::DetachableCommands(DetachableCommands::Run(e))) => {
-            name = e.name.unwrap();
+            name = e.pipeline.name.unwrap();
+            selection = e.selection;
         }
         _ => {
             let message = "Couldn't retrieve pipeline name";
@@ -30,6 +33,12 @@ pub fn launch() -> Result<()> {
     };
 
     let mut pipeline = Pipeline::get_by_name(&name)?;
+    pipeline.select_steps(
+        &selection.steps,
+        &selection.skip,
+        selection.from.as_deref(),
+        selection.until.as_deref(),
+    )?;
     let config = Config::get()?;
 
     // Guard
@@ -76,6 +85,8 @@ pub fn launch() -> Result<()> {
     match args.attach.clone() {
         Some(val) => {
             if val == String::from(&Attach::True) {
+                // Stream outputs to the terminal while running
+                *STREAM.lock().unwrap() = true;
                 pipeline.run()?;
                 // Return pipeline log
                 println!("{}", Node::from(&pipeline));
@@ -95,6 +106,11 @@ pub fn launch() -> Result<()> {
                         let message = "Pipeline status: Aborted";
                         Err(Error::msg(message))
                     }
+                    Some(Status::TimedOut) => {
+                        *EXIT_CODE.lock().unwrap() = ExitCode::FAILURE;
+                        let message = "Pipeline status: TimedOut";
+                        Err(Error::msg(message))
+                    }
                     _ => Ok(()),
                 };
             }
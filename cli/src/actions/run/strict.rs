@@ -1,5 +1,5 @@
 // Struct
-use crate::types::{Commands, DetachableCommands, PostCommands};
+use crate::types::{Commands, DetachableCommands, PostCommands, Selection};
 use pipelight_exec::Status;
 use workflow::{Getters, Node, Pipeline};
 // Globals
@@ -7,6 +7,7 @@ use crate::globals::CLI;
 use crate::types::verbosity::{level_value, Verbosity};
 use log::LevelFilter;
 use pipelight_utils::globals::LOGGER;
+use workflow::globals::STREAM;
 
 // Error Handling
 use miette::{Error, Result};
@@ -16,9 +17,11 @@ pub fn launch() -> Result<()> {
 
     // Retrieve command line args
     let name: String;
+    let selection: Selection;
     match args.commands {
         Commands::PostCommands(PostCommands::DetachableCommands(DetachableCommands::Run(e))) => {
-            name = e.name.unwrap();
+            name = e.pipeline.name.unwrap();
+            selection = e.selection;
         }
         _ => {
             let message = "Couldn.t retrieve pipeline name";
@@ -27,6 +30,12 @@ pub fn launch() -> Result<()> {
     };
 
     let mut pipeline = Pipeline::get_by_name(&name)?;
+    pipeline.select_steps(
+        &selection.steps,
+        &selection.skip,
+        selection.from.as_deref(),
+        selection.until.as_deref(),
+    )?;
     let config = workflow::Config::get()?;
 
     // Guard
@@ -51,6 +60,8 @@ pub fn launch() -> Result<()> {
     }
 
     // Action
+    // Stream outputs to the terminal while running
+    *STREAM.lock().unwrap() = true;
     pipeline.run()?;
     // Return pipeline log
     println!("{}", Node::from(&pipeline));
@@ -61,6 +72,10 @@ pub fn launch() -> Result<()> {
             let message = "Pipeline status: Failed";
             Err(Error::msg(message))
         }
+        Some(Status::TimedOut) => {
+            let message = "Pipeline status: TimedOut";
+            Err(Error::msg(message))
+        }
         _ => Ok(()),
     }
 }
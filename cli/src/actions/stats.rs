@@ -0,0 +1,21 @@
+// Struct
+use workflow::pipeline::Query;
+use workflow::{Logs, Stats};
+// Error Handling
+use miette::{IntoDiagnostic, Result};
+
+/**
+Print statistics over the pipeline runs that match the query,
+as tables or as json.
+*/
+pub fn launch(query: &Query, json: bool) -> Result<()> {
+    let pipelines = Logs::query(query)?;
+    let stats = Stats::from(&pipelines);
+    if json {
+        let stats_json = serde_json::to_string_pretty::<Stats>(&stats).into_diagnostic()?;
+        println!("{}", stats_json);
+    } else {
+        stats.display()?;
+    }
+    Ok(())
+}
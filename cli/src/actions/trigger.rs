@@ -1,7 +1,9 @@
 // Struct
 use crate::services::{Action, FgBg, Service};
 use crate::types::{verbosity::level_value, Verbosity};
-use crate::types::{Attach, Commands, DetachableCommands, Pipeline, PostCommands, Trigger};
+use crate::types::{
+    Attach, Commands, DetachableCommands, Pipeline, PostCommands, Run, Selection, Trigger,
+};
 
 use workflow;
 // Traits
@@ -73,9 +75,12 @@ pub fn launch() -> Result<()> {
             // LOGGER.lock().unwrap().set_level(&args.verbose)?;
         }
         args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
-            DetachableCommands::Run(Pipeline {
-                trigger: trigger.to_owned(),
-                name: Some(pipeline.name.clone()),
+            DetachableCommands::Run(Run {
+                pipeline: Pipeline {
+                    trigger: trigger.to_owned(),
+                    name: Some(pipeline.name.clone()),
+                },
+                selection: Selection::default(),
             }),
         ));
         Service::new(Action::RunLoose, Some(args))
@@ -1,7 +1,7 @@
 // Struct
 use super::{Action, Service};
 use crate::types::{Commands, DetachableCommands, PostCommands};
-use crate::types::{Pipeline, Trigger};
+use crate::types::{Pipeline, Run, Selection, Trigger};
 use pipelight_utils::git::Flag;
 // Error Handling
 use miette::Result;
@@ -21,6 +21,7 @@ impl Parser for Service {
         let mut flag = Some(String::from(&Flag::default()));
         // Run options
         let mut name = None;
+        let mut selection = Selection::default();
 
         // Retrieve reusable arguments and mutate the defaults
         if let Some(args) = self.args.clone() {
@@ -31,9 +32,10 @@ impl Parser for Service {
                     DetachableCommands::Trigger(trigger) => {
                         flag = trigger.flag;
                     }
-                    DetachableCommands::Run(pipeline) => {
-                        flag = pipeline.trigger.flag;
-                        name = pipeline.name;
+                    DetachableCommands::Run(run) => {
+                        flag = run.pipeline.trigger.flag;
+                        name = run.pipeline.name;
+                        selection = run.selection;
                     }
                     _ => {}
                 }
@@ -45,9 +47,12 @@ impl Parser for Service {
             Action::RunStrict => {
                 if let Some(ref mut args) = self.args {
                     args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
-                        DetachableCommands::Run(Pipeline {
-                            trigger: Trigger { flag },
-                            name,
+                        DetachableCommands::Run(Run {
+                            pipeline: Pipeline {
+                                trigger: Trigger { flag },
+                                name,
+                            },
+                            selection,
                         }),
                     ));
                 }
@@ -55,13 +60,18 @@ impl Parser for Service {
             Action::RunLoose => {
                 if let Some(ref mut args) = self.args {
                     args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
-                        DetachableCommands::Run(Pipeline {
-                            trigger: Trigger { flag },
-                            name,
+                        DetachableCommands::Run(Run {
+                            pipeline: Pipeline {
+                                trigger: Trigger { flag },
+                                name,
+                            },
+                            selection,
                         }),
                     ));
                 }
             }
+            // Retry arguments are reused as is
+            Action::Retry => {}
             Action::Trigger => {
                 if let Some(ref mut args) = self.args {
                     args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
@@ -17,6 +17,7 @@ impl Exec for Service {
         match self.cmd {
             Action::RunStrict => actions::run::strict::launch()?,
             Action::RunLoose => actions::run::loose::launch()?,
+            Action::Retry => actions::retry::launch()?,
             Action::Trigger => actions::trigger::launch()?,
             Action::Watch => actions::watch::launch()?,
         };
@@ -20,6 +20,7 @@ use miette::Result;
 pub enum Action {
     RunStrict,
     RunLoose,
+    Retry,
     Trigger,
     Watch,
 }
@@ -61,8 +62,8 @@ impl fmt::Display for Service {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::types::Pipeline;
     use crate::types::{Attach, Cli, Commands, DetachableCommands, PostCommands};
+    use crate::types::{Pipeline, Run};
 
     use assert_cmd::prelude::*; // Add methods on commands
     use std::process::Command; // Run commnds
@@ -73,9 +74,12 @@ mod test {
     fn make_dummy_service() -> Result<Service> {
         let args = Cli {
             commands: Commands::PostCommands(PostCommands::DetachableCommands(
-                DetachableCommands::Run(Pipeline {
-                    name: Some("test".to_owned()),
-                    ..Pipeline::default()
+                DetachableCommands::Run(Run {
+                    pipeline: Pipeline {
+                        name: Some("test".to_owned()),
+                        ..Pipeline::default()
+                    },
+                    ..Run::default()
                 }),
             )),
             attach: Some(String::from(&Attach::True)),
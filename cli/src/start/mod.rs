@@ -1,8 +1,8 @@
 // Struct
-use crate::actions::{logs, prompt, stop};
+use crate::actions::{logs, prompt, retry, stats, stop};
 use crate::services::{Action, Service};
 use crate::types::Cli;
-use crate::types::{ColoredOutput, LogsCommands, ToggleCommands};
+use crate::types::{ColoredOutput, LogsCommands, LogsFormat, ToggleCommands};
 use crate::types::{Commands, DetachableCommands, PostCommands, PreCommands};
 use pipelight_watcher::Watcher;
 use workflow::{Getters, Pipeline};
@@ -84,18 +84,24 @@ impl DetachableCommands {
         let mut args = CLI.lock().unwrap().clone();
         match self {
             DetachableCommands::Run(e) => {
-                if e.name.is_none() {
-                    e.name = Some(prompt::pipeline()?);
+                if e.pipeline.name.is_none() {
+                    e.pipeline.name = Some(prompt::pipeline()?);
                     args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
                         DetachableCommands::Run(e.to_owned()),
                     ))
                 }
-                if let Some(name) = e.name.clone() {
+                if let Some(name) = e.pipeline.name.clone() {
                     // Usefull SafeGuard that early returns fancy error
                     // if pipeline name not found
                     // on detach mode
-                    let pipeline = Pipeline::get_by_name(&name)?;
+                    let mut pipeline = Pipeline::get_by_name(&name)?;
                     pipeline.is_triggerable()?;
+                    pipeline.select_steps(
+                        &e.selection.steps,
+                        &e.selection.skip,
+                        e.selection.from.as_deref(),
+                        e.selection.until.as_deref(),
+                    )?;
 
                     Service::new(Action::RunLoose, Some(args))?.should_detach()?;
                 }
@@ -123,6 +129,12 @@ impl PostCommands {
                     stop::launch(&name)?;
                 }
             }
+            PostCommands::Retry(e) => {
+                // Early return fancy errors on detach mode
+                retry::get_parent(&e.target)?;
+                let args = CLI.lock().unwrap().clone();
+                Service::new(Action::Retry, Some(args))?.should_detach()?;
+            }
             PostCommands::Logs(e) => {
                 if let Some(commands) = e.commands.clone() {
                     match commands {
@@ -130,6 +142,10 @@ impl PostCommands {
                             logs::clean()?;
                             return Ok(());
                         }
+                        LogsCommands::Prune(e) => {
+                            logs::prune(e.dry_run)?;
+                            return Ok(());
+                        }
                     };
                 }
                 // Set colors
@@ -140,12 +156,21 @@ impl PostCommands {
                         ColoredOutput::Auto => {}
                     }
                 }
-                if e.display.json {
-                    logs::json(e.display.name.clone())?;
+                if e.follow {
+                    logs::follow(e.display.name.clone())?;
                 } else {
-                    logs::pretty(e.display.name.clone())?;
+                    let query = logs::get_query(e.display.name.clone(), &e.filters)?;
+                    match (&e.format, e.display.json) {
+                        (Some(LogsFormat::Junit), _) => logs::junit(&query)?,
+                        (Some(LogsFormat::Json), _) | (None, true) => logs::json(&query)?,
+                        _ => logs::pretty(&query)?,
+                    }
                 }
             }
+            PostCommands::Stats(e) => {
+                let query = logs::get_query(e.display.name.clone(), &e.filters)?;
+                stats::launch(&query, e.display.json)?;
+            }
             PostCommands::Ls(e) => {
                 if e.name.is_some() {
                     if e.json {
@@ -36,6 +36,17 @@ mod cli {
         Ok(())
     }
     #[test]
+    /// Filters and formats are not applied when following logs
+    fn logs_follow_filtered() -> Result<()> {
+        let mut cmd = Command::cargo_bin("pipelight").into_diagnostic()?;
+        cmd.arg("logs")
+            .arg("--follow")
+            .arg("--status")
+            .arg("failed");
+        cmd.assert().failure();
+        Ok(())
+    }
+    #[test]
     /// Generate auto completion even when no config file present
     fn make_completion() -> Result<()> {
         let mut cmd = Command::cargo_bin("pipelight").into_diagnostic()?;
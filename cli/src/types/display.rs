@@ -1,7 +1,7 @@
 // Structs
 use crate::types::{
-    Attach, Cli, DisplayCommands, Init, Logs, LogsCommands, Pipeline, Shell, Toggle,
-    ToggleCommands, Trigger,
+    Attach, Cli, DisplayCommands, Init, Logs, LogsCommands, LogsFilters, Pipeline, Retry, Run,
+    Selection, Shell, Stats, Toggle, ToggleCommands, Trigger,
 };
 use crate::types::{Commands, DetachableCommands, PostCommands, PreCommands};
 use crate::types::{InternalVerbosity, Verbosity};
@@ -51,12 +51,14 @@ impl fmt::Display for Commands {
             },
             Commands::PostCommands(post_commands) => match post_commands {
                 PostCommands::DetachableCommands(detachable_command) => match detachable_command {
-                    DetachableCommands::Run(pipeline) => format!("run{}", pipeline),
+                    DetachableCommands::Run(run) => format!("run{}", run),
                     DetachableCommands::Trigger(trigger) => format!("trigger{}", trigger),
                     DetachableCommands::Watch => "watch".to_owned(),
                 },
                 PostCommands::Stop(pipeline) => format!("stop{}", pipeline),
+                PostCommands::Retry(retry) => format!("retry{}", retry),
                 PostCommands::Logs(logs) => format!("logs{}", logs),
+                PostCommands::Stats(stats) => format!("stats{}", stats),
                 PostCommands::Inspect(pipeline) => format!("inspect{}", pipeline),
                 PostCommands::Ls(list) => format!("ls{}", list),
             },
@@ -70,14 +72,6 @@ impl fmt::Display for Pipeline {
         let mut string = "".to_owned();
 
         if self.name.is_some() {
-            #[cfg(not(target_os = "macos"))]
-            fn escape(name: &str) -> String {
-                return format!("{:?}", name);
-            }
-            #[cfg(target_os = "macos")]
-            fn escape(name: &str) -> String {
-                return format!("{}", name);
-            }
             string += " ";
             string += &escape(&self.name.clone().unwrap());
         }
@@ -90,6 +84,55 @@ impl fmt::Display for Pipeline {
         write!(f, "{}", string)
     }
 }
+impl fmt::Display for Run {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.pipeline, self.selection)
+    }
+}
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut string = "".to_owned();
+        for step in &self.steps {
+            string += " ";
+            string += "--step";
+            string += " ";
+            string += &escape(step);
+        }
+        for step in &self.skip {
+            string += " ";
+            string += "--skip";
+            string += " ";
+            string += &escape(step);
+        }
+        if let Some(from) = &self.from {
+            string += " ";
+            string += "--from";
+            string += " ";
+            string += &escape(from);
+        }
+        if let Some(until) = &self.until {
+            string += " ";
+            string += "--until";
+            string += " ";
+            string += &escape(until);
+        }
+        write!(f, "{}", string)
+    }
+}
+impl fmt::Display for Retry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut string = "".to_owned();
+        string += " ";
+        string += &self.target;
+        if let Some(from) = &self.from {
+            string += " ";
+            string += "--from";
+            string += " ";
+            string += from;
+        }
+        write!(f, "{}", string)
+    }
+}
 impl fmt::Display for DisplayCommands {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut string = "".to_owned();
@@ -127,9 +170,64 @@ impl fmt::Display for Logs {
                     string += " ";
                     string += "rm";
                 }
+                LogsCommands::Prune(e) => {
+                    string += " ";
+                    string += "prune";
+                    if e.dry_run {
+                        string += " ";
+                        string += "--dry-run";
+                    }
+                }
             }
             string += &format!("{}", &self.display);
         }
+        string += &format!("{}", self.filters);
+        if let Some(format) = &self.format {
+            string += " ";
+            string += "--format";
+            string += " ";
+            string += &String::from(format);
+        }
+        if self.follow {
+            string += " ";
+            string += "--follow";
+        }
+        write!(f, "{}", string)
+    }
+}
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut string = format!("{}", self.display);
+        string += &format!("{}", self.filters);
+        write!(f, "{}", string)
+    }
+}
+impl fmt::Display for LogsFilters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut string = "".to_owned();
+        let args = [
+            ("--status", &self.status),
+            ("--branch", &self.branch),
+            ("--tag", &self.tag),
+            ("--action", &self.action),
+            ("--since", &self.since),
+            ("--until", &self.until),
+            ("--commit", &self.commit),
+        ];
+        for (arg, value) in args {
+            if let Some(value) = value {
+                string += " ";
+                string += arg;
+                string += " ";
+                string += &escape(value);
+            }
+        }
+        if let Some(limit) = self.limit {
+            string += " ";
+            string += "--limit";
+            string += " ";
+            string += &limit.to_string();
+        }
         write!(f, "{}", string)
     }
 }
@@ -166,6 +264,18 @@ impl fmt::Display for Trigger {
     }
 }
 
+/**
+Quote names that may contain whitespaces.
+*/
+#[cfg(not(target_os = "macos"))]
+fn escape(name: &str) -> String {
+    format!("{:?}", name)
+}
+#[cfg(target_os = "macos")]
+fn escape(name: &str) -> String {
+    name.to_owned()
+}
+
 fn from_internal_verbosity_to_string(e: InternalVerbosity) -> String {
     let mut string = "".to_owned();
     if e.is_silent() {
@@ -195,7 +305,8 @@ fn from_verbosity_to_string(e: Verbosity) -> String {
 mod display {
     // Structs
     use crate::types::{
-        Attach, Cli, DisplayCommands, Init, Logs, LogsCommands, Pipeline, Shell, Toggle, Trigger,
+        Attach, Cli, DisplayCommands, Init, Logs, LogsCommands, LogsFilters, LogsFormat, Pipeline,
+        Prune, Retry, Run, Selection, Shell, Stats, Toggle, Trigger,
     };
     use crate::types::{Commands, DetachableCommands, PostCommands, PreCommands};
     use crate::types::{InternalVerbosity, Verbosity};
@@ -206,11 +317,14 @@ mod display {
         // Define a cli struct
         let cli = Cli {
             commands: Commands::PostCommands(PostCommands::DetachableCommands(
-                DetachableCommands::Run(Pipeline {
-                    name: Some("test".to_owned()),
-                    trigger: Trigger {
-                        flag: Some("pre-push".to_owned()),
+                DetachableCommands::Run(Run {
+                    pipeline: Pipeline {
+                        name: Some("test".to_owned()),
+                        trigger: Trigger {
+                            flag: Some("pre-push".to_owned()),
+                        },
                     },
+                    selection: Selection::default(),
                 }),
             )),
             attach: Some(String::from(&Attach::False)),
@@ -225,16 +339,50 @@ mod display {
         assert_eq!(result, "run \"test\" --flag pre-push");
     }
     #[test]
+    fn selection_args() {
+        // Define a cli struct
+        let cli = Cli {
+            commands: Commands::PostCommands(PostCommands::DetachableCommands(
+                DetachableCommands::Run(Run {
+                    pipeline: Pipeline {
+                        name: Some("test".to_owned()),
+                        trigger: Trigger { flag: None },
+                    },
+                    selection: Selection {
+                        steps: vec!["build".to_owned(), "test".to_owned()],
+                        skip: vec![],
+                        from: None,
+                        until: Some("deploy".to_owned()),
+                    },
+                }),
+            )),
+            attach: Some(String::from(&Attach::False)),
+            raw: None,
+            config: None,
+            internal_verbose: InternalVerbosity::new(0, 0),
+            verbose: Verbosity::new(0, 0),
+        };
+        let result = format!("{}", cli);
+        println!("\n{}", result);
+        assert_eq!(
+            result,
+            "run \"test\" --step \"build\" --step \"test\" --until \"deploy\""
+        );
+    }
+    #[test]
     fn logs_args() {
         // Define a cli struct
         let cli = Cli {
             commands: Commands::PostCommands(PostCommands::Logs(Logs {
                 commands: Some(LogsCommands::Rm),
+                follow: false,
                 display: DisplayCommands {
                     json: false,
                     name: None,
                     color: None,
                 },
+                filters: LogsFilters::default(),
+                format: None,
             })),
             attach: Some(String::from(&Attach::False)),
             raw: None,
@@ -247,6 +395,108 @@ mod display {
         assert_eq!(result, "logs rm");
     }
     #[test]
+    fn logs_prune_args() {
+        // Define a cli struct
+        let cli = Cli {
+            commands: Commands::PostCommands(PostCommands::Logs(Logs {
+                commands: Some(LogsCommands::Prune(Prune { dry_run: true })),
+                follow: false,
+                display: DisplayCommands {
+                    json: false,
+                    name: None,
+                    color: None,
+                },
+                filters: LogsFilters::default(),
+                format: None,
+            })),
+            attach: Some(String::from(&Attach::False)),
+            raw: None,
+            config: None,
+            internal_verbose: InternalVerbosity::new(0, 0),
+            verbose: Verbosity::new(0, 0),
+        };
+        let result = format!("{}", cli);
+        println!("\n{}", result);
+        assert_eq!(result, "logs prune --dry-run");
+    }
+    #[test]
+    fn stats_args() {
+        // Define a cli struct
+        let cli = Cli {
+            commands: Commands::PostCommands(PostCommands::Stats(Stats {
+                display: DisplayCommands {
+                    json: true,
+                    name: Some("test".to_owned()),
+                    color: None,
+                },
+                filters: LogsFilters {
+                    branch: Some("main".to_owned()),
+                    ..Default::default()
+                },
+            })),
+            attach: Some(String::from(&Attach::False)),
+            raw: None,
+            config: None,
+            internal_verbose: InternalVerbosity::new(0, 0),
+            verbose: Verbosity::new(0, 0),
+        };
+        let result = format!("{}", cli);
+        println!("\n{}", result);
+        assert_eq!(result, "stats test --json --branch \"main\"");
+    }
+    #[test]
+    fn logs_filters_args() {
+        // Define a cli struct
+        let cli = Cli {
+            commands: Commands::PostCommands(PostCommands::Logs(Logs {
+                commands: None,
+                follow: false,
+                display: DisplayCommands {
+                    json: false,
+                    name: None,
+                    color: None,
+                },
+                filters: LogsFilters {
+                    status: Some("failed".to_owned()),
+                    branch: Some("main".to_owned()),
+                    since: Some("P7D".to_owned()),
+                    limit: Some(5),
+                    ..Default::default()
+                },
+                format: Some(LogsFormat::Junit),
+            })),
+            attach: Some(String::from(&Attach::False)),
+            raw: None,
+            config: None,
+            internal_verbose: InternalVerbosity::new(0, 0),
+            verbose: Verbosity::new(0, 0),
+        };
+        let result = format!("{}", cli);
+        println!("\n{}", result);
+        assert_eq!(
+            result,
+            "logs --status \"failed\" --branch \"main\" --since \"P7D\" --limit 5 --format junit"
+        );
+    }
+    #[test]
+    fn retry_args() {
+        // Define a cli struct
+        let cli = Cli {
+            commands: Commands::PostCommands(PostCommands::Retry(Retry {
+                target: "test".to_owned(),
+                from: Some("build".to_owned()),
+            })),
+            attach: Some(String::from(&Attach::False)),
+            raw: None,
+            config: None,
+            internal_verbose: InternalVerbosity::new(0, 0),
+            verbose: Verbosity::new(0, 0),
+        };
+        let result = format!("{}", cli);
+        println!("\n{}", result);
+        assert_eq!(result, "retry test --from build");
+    }
+    #[test]
     fn internal_verbosity() {
         // Define a cli struct
         let cli = Cli {
@@ -2,7 +2,9 @@ use crate::types::{Attach, ColoredOutput};
 use convert_case::{Case, Casing};
 // Structs
 use crate::types::Commands;
-use crate::types::{Cli, DisplayCommands, Init, Logs, Pipeline, Shell, Toggle, Trigger};
+use crate::types::{
+    Cli, DisplayCommands, Init, Logs, LogsFormat, Pipeline, Shell, Toggle, Trigger,
+};
 
 impl From<&String> for Attach {
     fn from(option: &String) -> Attach {
@@ -59,6 +61,11 @@ impl From<&Init> for String {
         format!("{}", &e)
     }
 }
+impl From<&LogsFormat> for String {
+    fn from(format: &LogsFormat) -> String {
+        serde_plain::to_string::<LogsFormat>(format).unwrap()
+    }
+}
 impl From<&Logs> for String {
     fn from(e: &Logs) -> String {
         format!("{}", &e)
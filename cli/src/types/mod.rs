@@ -3,7 +3,7 @@ pub mod display;
 pub mod from_string;
 
 // Clap - command line lib
-use clap::{Parser, Subcommand, ValueHint};
+use clap::{Parser, Subcommand, ValueEnum, ValueHint};
 // Verbosity
 pub mod verbosity;
 pub use verbosity::{InternalVerbosity, Verbosity};
@@ -162,8 +162,12 @@ pub enum PostCommands {
     DetachableCommands(DetachableCommands),
     /// Stop the pipeline execution and its every child processes
     Stop(Pipeline),
+    /// Retry a previous pipeline run from its first failed step
+    Retry(Retry),
     /// Display pipelines logs
     Logs(Logs),
+    /// Display success rates, durations and flaky steps computed from logs
+    Stats(Stats),
     /// List available pipelines with a few more useful informations
     Ls(DisplayCommands),
     /// Displays pipelines with the maximum verbosity level (interactive)
@@ -176,7 +180,7 @@ Commands that can be run in background
 #[derive(Debug, Clone, Eq, PartialEq, Subcommand)]
 pub enum DetachableCommands {
     /// Run a pipeline (interactive)
-    Run(Pipeline),
+    Run(Run),
     /// Manualy trigger pipelines
     Trigger(Trigger),
     /// Launch a watcher on the working directory (debugging)
@@ -214,6 +218,52 @@ impl Default for Pipeline {
     }
 }
 
+/**
+Arguments to run a pipeline.
+- pipeline: the pipeline name and its triggering environment,
+- selection: the subset of steps to run.
+*/
+#[derive(Default, Debug, Clone, Eq, PartialEq, Parser)]
+pub struct Run {
+    #[command(flatten)]
+    pub pipeline: Pipeline,
+    #[command(flatten)]
+    pub selection: Selection,
+}
+
+/**
+Arguments to run only a subset of the pipeline steps.
+*/
+#[derive(Default, Debug, Clone, Eq, PartialEq, Parser)]
+pub struct Selection {
+    /// Only run this step (repeatable)
+    #[arg(long = "step", value_name = "NAME")]
+    pub steps: Vec<String>,
+    /// Do not run this step (repeatable)
+    #[arg(long, value_name = "NAME")]
+    pub skip: Vec<String>,
+    /// Run the steps starting from this one
+    #[arg(long, value_name = "NAME")]
+    pub from: Option<String>,
+    /// Run the steps up to this one
+    #[arg(long, value_name = "NAME")]
+    pub until: Option<String>,
+}
+
+/**
+Arguments to retry a previous pipeline run.
+- target: the run uuid, or a pipeline name to retry its last run,
+- from: the step to resume the run from.
+*/
+#[derive(Debug, Clone, Eq, PartialEq, Parser)]
+pub struct Retry {
+    /// The run uuid or the pipeline name
+    pub target: String,
+    /// Resume from this step instead of the first failed one
+    #[arg(long)]
+    pub from: Option<String>,
+}
+
 /**
 Arguments to set/modify the triggering environment.
 */
@@ -232,15 +282,99 @@ pub struct Logs {
     #[command(subcommand)]
     pub commands: Option<LogsCommands>,
 
+    /// Follow the last pipeline run until it ends
+    #[arg(long, short, conflicts_with_all = [
+        "status", "branch", "tag", "action", "since", "until", "commit", "limit", "json", "format"
+    ])]
+    pub follow: bool,
+
     /// Display logs in json format
     #[command(flatten)]
     pub display: DisplayCommands,
+
+    /// Filter pipeline runs
+    #[command(flatten)]
+    pub filters: LogsFilters,
+
+    /// The output format
+    #[arg(long, value_enum)]
+    pub format: Option<LogsFormat>,
+}
+
+/**
+The formats logs can be displayed in.
+*/
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogsFormat {
+    /// Pretty tree
+    Tree,
+    Json,
+    /// JUnit XML, a pipeline run as a testsuite and a step as a testcase
+    Junit,
+}
+
+/**
+Arguments to compute statistics over pipeline runs.
+*/
+#[derive(Debug, Clone, Eq, PartialEq, Parser)]
+pub struct Stats {
+    /// Display statistics in json format
+    #[command(flatten)]
+    pub display: DisplayCommands,
+
+    /// Filter pipeline runs
+    #[command(flatten)]
+    pub filters: LogsFilters,
+}
+
+/**
+Arguments to select pipeline runs from logs.
+*/
+#[derive(Default, Debug, Clone, Eq, PartialEq, Parser)]
+pub struct LogsFilters {
+    /// Only runs with this status (ex: failed, succeeded, aborted)
+    #[arg(long)]
+    pub status: Option<String>,
+    /// Only runs triggered on this git branch
+    #[arg(long)]
+    pub branch: Option<String>,
+    /// Only runs triggered on this git tag
+    #[arg(long)]
+    pub tag: Option<String>,
+    /// Only runs triggered by this action (ex: pre-push, manual)
+    #[arg(long)]
+    pub action: Option<String>,
+    /// Only runs triggered after this date (ex: 2024-05-01, P7D for a week ago)
+    #[arg(long)]
+    pub since: Option<String>,
+    /// Only runs triggered before this date (ex: 2024-05-01 up to its end, P7D for a week ago)
+    #[arg(long)]
+    pub until: Option<String>,
+    /// Only runs triggered on this commit (or a commit hash prefix)
+    #[arg(long)]
+    pub commit: Option<String>,
+    /// Only the given number of most recent runs
+    #[arg(long)]
+    pub limit: Option<usize>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Parser)]
 pub enum LogsCommands {
     /// Clear logs
     Rm,
+    /// Delete the runs that fall out of the config file retention policy
+    Prune(Prune),
+}
+
+/**
+Arguments to prune logs.
+*/
+#[derive(Debug, Clone, Eq, PartialEq, Parser)]
+pub struct Prune {
+    /// List the runs to delete without deleting them
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Parser)]
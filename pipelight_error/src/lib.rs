@@ -66,12 +66,58 @@ pub enum CastError {
     #[diagnostic(transparent)]
     HclError(#[from] HclError),
 
+    // Rules
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    RuleError(#[from] RuleError),
+
     // Serialize
     #[error(transparent)]
     #[diagnostic(code(serialize::toml))]
     TomlSerError(#[from] toml::ser::Error),
 }
 
+/**
+A report type for configuration files that deserialize fine
+but break a pipeline definition rule (ex: dependency cycles).
+*/
+#[derive(Error, Diagnostic, Debug)]
+#[diagnostic(code(cast::rule))]
+#[error("{}", message)]
+pub struct RuleError {
+    pub message: String,
+    #[help]
+    pub help: String,
+    #[label("here")]
+    pub at: SourceSpan,
+    #[source_code]
+    pub src: String,
+}
+impl RuleError {
+    /**
+    Point the span at the first occurence of the given keywords
+    found in sequence in the source (ex: a step name and then its "needs" field).
+    */
+    pub fn new(message: &str, help: &str, src: &str, keywords: &[&str]) -> Self {
+        let mut at = SourceSpan::new(0.into(), 0);
+        let mut offset = 0;
+        for keyword in keywords {
+            if let Some(index) = src[offset..].find(keyword) {
+                offset += index;
+                at = SourceSpan::new(offset.into(), keyword.len());
+            } else {
+                break;
+            }
+        }
+        RuleError {
+            message: message.to_owned(),
+            help: help.to_owned(),
+            at,
+            src: src.to_owned(),
+        }
+    }
+}
+
 /**
 A JSON report type with hint, colors and code span.
 For better configuration file debugging
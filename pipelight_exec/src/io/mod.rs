@@ -4,8 +4,7 @@ use uuid::Uuid;
 use crate::globals::OUTDIR;
 // File manipulation
 use std::fs::{remove_dir_all, File};
-use std::io::BufReader;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 use std::process::Output;
 
@@ -23,6 +22,9 @@ pub struct Io {
     pub stdin: Option<String>,
     pub stdout: Option<String>,
     pub stderr: Option<String>,
+    // Whether the outputs are not text (invalid utf-8 or null bytes),
+    // in which case they have been lossily decoded.
+    pub is_binary: Option<bool>,
 }
 
 impl Io {
@@ -30,7 +32,15 @@ impl Io {
     Delete the files associated to the Io struct.
     */
     pub fn clean(&self) -> Result<(), std::io::Error> {
-        let path = format!("{}/{}", *OUTDIR.lock().unwrap(), self.uuid);
+        let directory = OUTDIR.lock().unwrap().clone();
+        self.clean_in(&directory)
+    }
+    /**
+    Delete the files associated to the Io struct,
+    from the provided outputs directory.
+    */
+    pub fn clean_in(&self, directory: &str) -> Result<(), std::io::Error> {
+        let path = format!("{}/{}", directory, self.uuid);
         // Guard
         let path = Path::new(&path);
         if path.exists() {
@@ -38,6 +48,22 @@ impl Io {
         }
         Ok(())
     }
+    /**
+    Return the paths of the files the process stdout and stderr are written to.
+    */
+    pub fn get_paths(&self) -> (String, String) {
+        let directory = OUTDIR.lock().unwrap().clone();
+        self.get_paths_in(&directory)
+    }
+    /**
+    Return the paths of the files the process stdout and stderr are written to,
+    inside the provided outputs directory.
+    */
+    pub fn get_paths_in(&self, directory: &str) -> (String, String) {
+        let stdout_path = format!("{}/{}/1", directory, self.uuid);
+        let stderr_path = format!("{}/{}/2", directory, self.uuid);
+        (stdout_path, stderr_path)
+    }
     /**
      * Read the process stdout and stderr and stores it in the struct field
      *
@@ -56,43 +82,137 @@ impl Io {
      *  
      */
     pub fn read(&mut self) -> Result<(), std::io::Error> {
+        self.read_capped(None)
+    }
+    /**
+     * Read the process stdout and stderr like `read()`,
+     * but only keep the head and the tail of outputs bigger than the provided size.
+     * The full outputs are left untouched in the files.
+     */
+    pub fn read_capped(&mut self, max: Option<usize>) -> Result<(), std::io::Error> {
         // path definition
-        let stdout_path = format!("{}/{}/1", *OUTDIR.lock().unwrap(), self.uuid);
-        let stderr_path = format!("{}/{}/2", *OUTDIR.lock().unwrap(), self.uuid);
+        let (stdout_path, stderr_path) = self.get_paths();
 
         // stdout
         info!("read subprocess stdout from tmp file at {}", stdout_path);
-        let f = File::open(stdout_path)?;
-        let mut buf_reader = BufReader::new(f);
-        let mut stdout = String::new();
-        buf_reader.read_to_string(&mut stdout)?;
+        let (stdout, stdout_is_binary) = read_file(&stdout_path, max)?;
 
         // stderr
         info!("Read subprocess stderr from tmp file at {}", stderr_path);
-        let f = File::open(stderr_path)?;
-        let mut buf_reader = BufReader::new(f);
-        let mut stderr = String::new();
-        buf_reader.read_to_string(&mut stderr)?;
+        let (stderr, stderr_is_binary) = read_file(&stderr_path, max)?;
 
         *self = Io {
             stdin: self.stdin.to_owned(),
             stdout: Some(stdout),
             stderr: Some(stderr),
+            is_binary: Some(stdout_is_binary || stderr_is_binary),
             ..*self
         };
         Ok(())
     }
+    /**
+     * Only keep the head and the tail of outputs bigger than the provided size.
+     */
+    pub fn truncate(&mut self, max: usize) {
+        let (stdout_path, stderr_path) = self.get_paths();
+        if let Some(stdout) = &self.stdout {
+            self.stdout = Some(truncate(stdout, max, &stdout_path));
+        }
+        if let Some(stderr) = &self.stderr {
+            self.stderr = Some(truncate(stderr, max, &stderr_path));
+        }
+    }
 }
+
+/**
+* The line inserted in place of the truncated part of an output.
+*/
+fn get_marker(skipped: usize, path: &str) -> String {
+    format!(
+        "\n[... {} bytes truncated, full output in {} ...]\n",
+        skipped, path
+    )
+}
+
+/**
+* Decode bytes into a string, replacing invalid sequences.
+* Returns the string and whether the bytes look binary.
+*
+* An incomplete sequence at the end of the bytes
+* (as found where an output has been cut) is not considered binary.
+*/
+fn decode(bytes: &[u8]) -> (String, bool) {
+    let is_binary =
+        bytes.contains(&0) || std::str::from_utf8(bytes).is_err_and(|e| e.error_len().is_some());
+    (String::from_utf8_lossy(bytes).into_owned(), is_binary)
+}
+
+/**
+* Read a whole output file, or only its head and tail if bigger than the provided size.
+*/
+fn read_file(path: &str, max: Option<usize>) -> Result<(String, bool), std::io::Error> {
+    let mut f = File::open(path)?;
+    let len = f.metadata()?.len() as usize;
+    match max {
+        Some(max) if len > max => {
+            let mut head = vec![0; max / 2];
+            f.read_exact(&mut head)?;
+            let mut tail = vec![];
+            f.seek(SeekFrom::Start((len - max / 2) as u64))?;
+            f.read_to_end(&mut tail)?;
+            // Skip the partial character the tail may start with.
+            let start = tail
+                .iter()
+                .take(3)
+                .take_while(|e| (**e & 0b1100_0000) == 0b1000_0000)
+                .count();
+            let (head, head_is_binary) = decode(&head);
+            let (tail, tail_is_binary) = decode(&tail[start..]);
+            let text = format!("{}{}{}", head, get_marker(len - max, path), tail);
+            Ok((text, head_is_binary || tail_is_binary))
+        }
+        _ => {
+            let mut bytes = vec![];
+            f.read_to_end(&mut bytes)?;
+            Ok(decode(&bytes))
+        }
+    }
+}
+
+/**
+* Only keep the head and the tail of a text bigger than the provided size.
+*/
+fn truncate(text: &str, max: usize, path: &str) -> String {
+    if text.len() <= max {
+        return text.to_owned();
+    }
+    let mut head = max / 2;
+    while !text.is_char_boundary(head) {
+        head -= 1;
+    }
+    let mut tail = text.len() - max / 2;
+    while !text.is_char_boundary(tail) {
+        tail += 1;
+    }
+    format!(
+        "{}{}{}",
+        &text[..head],
+        get_marker(tail - head, path),
+        &text[tail..]
+    )
+}
+
 /**
 * Convert a standart process (std::process) outputs into an Io struct.
-* The output buffers are converted into human readable strings.
+* The output buffers are converted into human readable strings,
+* invalid utf-8 sequences are replaced.
 */
 impl From<&Output> for Io {
     fn from(output: &Output) -> Io {
-        let stdout_str = String::from_utf8(output.stdout.to_owned()).unwrap();
+        let (stdout_str, stdout_is_binary) = decode(&output.stdout);
         let stdout_str = stdout_str.trim_matches('\n').trim_matches('\r');
 
-        let stderr_str = String::from_utf8(output.stderr.to_owned()).unwrap();
+        let (stderr_str, stderr_is_binary) = decode(&output.stderr);
         let stderr_str = stderr_str.trim_matches('\n').trim_matches('\r');
 
         let mut stdout = None;
@@ -108,6 +228,30 @@ impl From<&Output> for Io {
             uuid: Uuid::new_v4(),
             stdout,
             stderr,
+            is_binary: Some(stdout_is_binary || stderr_is_binary),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decode_binary() {
+        let (text, is_binary) = decode(&[0x66, 0x6f, 0xff, 0x00]);
+        assert_eq!(text, "fo\u{FFFD}\0");
+        assert!(is_binary);
+        // A character cut in half is not binary
+        let (_, is_binary) = decode(&"é".as_bytes()[..1]);
+        assert!(!is_binary);
+    }
+    #[test]
+    fn truncate_head_and_tail() {
+        let text = "a".repeat(10) + &"é".repeat(10) + &"z".repeat(10);
+        let res = truncate(&text, 20, "path");
+        assert!(res.starts_with("aaaaaaaaaa\n[... "));
+        assert!(res.ends_with("...]\nzzzzzzzzzz"));
+        assert_eq!(truncate("short", 20, "path"), "short");
+    }
+}
@@ -1,7 +1,9 @@
 // Unix process manipulation
-use rustix::process::{getgid, getpid, kill_process, test_kill_process, Signal};
+use rustix::process::{getgid, getpid, kill_process, test_kill_process, Pid, Signal};
+use std::fs;
+use std::{thread, time};
 use sysinfo::get_current_pid;
-use sysinfo::{Process, ProcessRefreshKind, ProcessesToUpdate, System};
+use sysinfo::{Process, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
 // Error handling
 use miette::{Context, IntoDiagnostic, Result};
 use pipelight_error::{LibError, PipelightError};
@@ -19,6 +21,10 @@ pub struct Finder {
     cwd: Option<String>,
     pid: Option<u32>,
     gid: Option<u32>,
+    // The process whose descendants are searched
+    ancestor: Option<u32>,
+    // An environment variable and its value
+    env: Option<(String, String)>,
     // Search results
     pub matches: Option<Vec<crate::Process>>,
 }
@@ -32,6 +38,8 @@ impl Default for Finder {
             seeds: None,
             pid: None,
             gid: None,
+            ancestor: None,
+            env: None,
             matches: None,
         }
     }
@@ -79,6 +87,55 @@ impl Finder {
         self.pid = Some(pid.to_owned());
         self.to_owned()
     }
+    /**
+     * Restrict search result to the descendants of a process
+     * (children, grand-children...), from the processes parent links.
+     */
+    pub fn descendants(&mut self, pid: &u32) -> Self {
+        self.ancestor = Some(pid.to_owned());
+        self.to_owned()
+    }
+    /**
+     * Restrict search result to the processes whose environment
+     * contains the variable with the provided value.
+     * Variables are inherited, so it finds processes that left the process tree
+     * (daemons, setsid...) as long as they kept their environment.
+     */
+    pub fn env(&mut self, key: &str, value: &str) -> Self {
+        self.env = Some((key.to_owned(), value.to_owned()));
+        self.to_owned()
+    }
+    /**
+     * Guard - Ensure the process descends from the searched ancestor
+     */
+    fn is_descendant(&self, process: &Process, s: &System) -> bool {
+        if let Some(ancestor) = self.ancestor {
+            let ancestor = sysinfo::Pid::from_u32(ancestor);
+            let mut parent = process.parent();
+            // Bounded walk, in case of a (racy) parent loop
+            for _ in 0..1024 {
+                match parent {
+                    Some(pid) if pid == ancestor => return true,
+                    Some(pid) => parent = s.process(pid).and_then(|e| e.parent()),
+                    None => return false,
+                }
+            }
+            false
+        } else {
+            true
+        }
+    }
+    /**
+     * Guard - Ensure the process environment contains the searched variable
+     */
+    fn is_match_env(&self, process: &Process) -> bool {
+        if let Some((key, value)) = &self.env {
+            let var = format!("{}={}", key, value);
+            process.environ().iter().any(|e| e.to_str() == Some(&var))
+        } else {
+            true
+        }
+    }
     /**
      * Guard - Ensure command contains some seed(string)
      */
@@ -133,14 +190,15 @@ impl Finder {
      */
     pub fn search(&mut self) -> Result<Self, PipelightError> {
         let mut s = System::new_all();
-        s.refresh_processes_specifics(
-            ProcessesToUpdate::All,
-            ProcessRefreshKind::new()
-                .without_cpu()
-                .without_memory()
-                .without_disk_usage()
-                .without_environ(),
-        );
+        let mut kind = ProcessRefreshKind::new()
+            .without_cpu()
+            .without_memory()
+            .without_disk_usage()
+            .without_environ();
+        if self.env.is_some() {
+            kind = kind.with_environ(UpdateKind::Always);
+        }
+        s.refresh_processes_specifics(ProcessesToUpdate::All, kind);
 
         // Loop through process list
         let mut matches: Vec<crate::Process> = vec![];
@@ -168,8 +226,20 @@ impl Finder {
                 // Guard - Ensure command contains some seed(string)
                 let cond_seed = self.is_match_seeds(process)?;
 
+                // Guard - Ensure process descends from the ancestor
+                let cond_descendant = self.is_descendant(process, &s);
+
+                // Guard - Ensure process has the environment variable
+                let cond_env = self.is_match_env(process);
+
                 // Final resolution
-                if cond_root && cond_pwd && cond_seed && cond_other_pid {
+                if cond_root
+                    && cond_pwd
+                    && cond_seed
+                    && cond_other_pid
+                    && cond_descendant
+                    && cond_env
+                {
                     matches.push(crate::Process::from(process));
                 }
             }
@@ -286,6 +356,55 @@ impl Finder {
         }
         Ok(())
     }
+    /**
+     * Terminate matches if any.
+     * They are first gently stopped (SIGTERM),
+     * and then killed (SIGKILL) if still alive after the grace period.
+     * Processes that exited meanwhile are ignored.
+     */
+    pub fn terminate(&self, grace: &time::Duration) {
+        let pids: Vec<Pid> = self
+            .matches
+            .iter()
+            .flatten()
+            .filter_map(|e| e.pid)
+            .filter_map(Pid::from_raw)
+            .collect();
+        for pid in &pids {
+            let _ = kill_process(*pid, Signal::Term);
+        }
+        let started_at = time::Instant::now();
+        loop {
+            let alive: Vec<&Pid> = pids.iter().filter(|e| is_alive(e)).collect();
+            if alive.is_empty() {
+                return;
+            }
+            if started_at.elapsed() >= *grace {
+                for pid in alive {
+                    let _ = kill_process(*pid, Signal::Kill);
+                }
+                return;
+            }
+            thread::sleep(time::Duration::from_millis(100));
+        }
+    }
+}
+
+/**
+* Whether the process is still running.
+* A zombie (dead but not yet reaped by its parent) is not.
+*/
+fn is_alive(pid: &Pid) -> bool {
+    if test_kill_process(*pid).is_err() {
+        return false;
+    }
+    // The state follows the command name, which is enclosed in parenthesis.
+    match fs::read_to_string(format!("/proc/{}/stat", pid.as_raw_nonzero())) {
+        Ok(stat) => !stat
+            .rsplit_once(')')
+            .is_some_and(|(_, e)| e.trim_start().starts_with('Z')),
+        Err(_) => true,
+    }
 }
 
 #[cfg(test)]
@@ -385,6 +504,36 @@ mod test {
         Ok(())
     }
     #[test]
+    fn terminate_tree() -> Result<()> {
+        // A child ignoring SIGTERM and a daemon that left the tree
+        let mut process = Process::new()
+            .stdin("trap '' TERM; setsid sleep 41 & sleep 42; wait")
+            .env("PIPELIGHT_TEST_TAG", "terminate_tree")
+            .term()
+            .background()
+            .to_owned();
+        process.run()?;
+        thread::sleep(time::Duration::from_millis(500));
+
+        let finder = Finder::new()
+            .env("PIPELIGHT_TEST_TAG", "terminate_tree")
+            .search()?;
+        assert!(finder.clone().matches.unwrap().len() >= 3);
+        finder.terminate(&time::Duration::from_millis(500));
+
+        let finder = Finder::new()
+            .env("PIPELIGHT_TEST_TAG", "terminate_tree")
+            .search()?;
+        let alive = finder
+            .matches
+            .iter()
+            .flatten()
+            .filter(|e| super::is_alive(&rustix::process::Pid::from_raw(e.pid.unwrap()).unwrap()))
+            .count();
+        assert_eq!(alive, 0);
+        Ok(())
+    }
+    #[test]
     fn different_cwd() -> Result<(), PipelightError> {
         let root = env::current_dir()?;
         let root = root.to_str().unwrap();
@@ -0,0 +1,53 @@
+// Unix process manipulation
+use rustix::process::{kill_process_group, Pid, Signal};
+use signal_hook::consts::signal::{SIGHUP, SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+use signal_hook::low_level::emulate_default_handler;
+use std::thread;
+// Global vars
+use once_cell::sync::Lazy;
+use std::sync::{Mutex, Once};
+
+/**
+* The process groups spawned out of the terminal foreground group,
+* that don't receive the terminal signals (Ctrl-C) along with pipelight.
+*/
+static GROUPS: Lazy<Mutex<Vec<Pid>>> = Lazy::new(|| Mutex::new(vec![]));
+static FORWARD: Once = Once::new();
+
+/**
+* Track a process group until it is released,
+* so that the interruption signals pipelight receives are forwarded to it.
+*/
+pub(super) fn register(pgid: Pid) {
+    FORWARD.call_once(forward_signals);
+    GROUPS.lock().unwrap().push(pgid);
+}
+
+/**
+* Stop tracking a process group, once its leader has exited.
+*/
+pub(super) fn release(pgid: Pid) {
+    GROUPS.lock().unwrap().retain(|e| *e != pgid);
+}
+
+/**
+* Forward the interruption signals to the tracked process groups in a background thread,
+* and then let the signal terminate pipelight as it would have without a handler.
+*/
+fn forward_signals() {
+    let mut signals = match Signals::new([SIGINT, SIGTERM, SIGHUP]) {
+        Ok(signals) => signals,
+        Err(_) => return,
+    };
+    thread::spawn(move || {
+        for sig in signals.forever() {
+            if let Some(signal) = Signal::from_raw(sig) {
+                for pgid in GROUPS.lock().unwrap().iter() {
+                    let _ = kill_process_group(*pgid, signal);
+                }
+            }
+            let _ = emulate_default_handler(sig);
+        }
+    });
+}
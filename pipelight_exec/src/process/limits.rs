@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+// Unix process manipulation
+use std::os::unix::process::ExitStatusExt;
+use std::process::ExitStatus;
+// Struct
+use crate::Usage;
+
+/**
+* Resource limits applied to the process right before it starts (see setrlimit(2)).
+* They are inherited by every child of the process.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Limits {
+    // The maximum virtual memory size in bytes.
+    pub memory: Option<u64>,
+    // The maximum CPU time in seconds.
+    pub cpu: Option<u64>,
+    // The maximum number of open file descriptors.
+    pub open_files: Option<u64>,
+    // The maximum size in bytes of a file written by the process.
+    pub file_size: Option<u64>,
+}
+
+/**
+* The limit a process has been terminated for.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Limit {
+    Memory,
+    Cpu,
+    FileSize,
+}
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::Memory => write!(f, "memory limit possibly exceeded"),
+            Limit::Cpu => write!(f, "cpu time limit exceeded"),
+            Limit::FileSize => write!(f, "file size limit exceeded"),
+        }
+    }
+}
+
+impl Limits {
+    /**
+     * Set the limits on the calling process.
+     *
+     * Runs in the forked child before exec,
+     * so it must only call async-signal-safe functions.
+     */
+    pub(super) fn apply(&self) -> Result<(), std::io::Error> {
+        // The cpu hard limit is a second above the soft one,
+        // so that the process first receives a catchable SIGXCPU.
+        let limits = [
+            (libc::RLIMIT_AS, self.memory, 0),
+            (libc::RLIMIT_CPU, self.cpu, 1),
+            (libc::RLIMIT_NOFILE, self.open_files, 0),
+            (libc::RLIMIT_FSIZE, self.file_size, 0),
+        ];
+        for (resource, value, grace) in limits {
+            let value = match value {
+                Some(value) => value as libc::rlim_t,
+                None => continue,
+            };
+            let mut rlimit = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            if unsafe { libc::getrlimit(resource, &mut rlimit) } == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            // An unprivileged process can't raise its hard limit.
+            rlimit.rlim_max = rlimit.rlim_max.min(value.saturating_add(grace));
+            rlimit.rlim_cur = value.min(rlimit.rlim_max);
+            if unsafe { libc::setrlimit(resource, &rlimit) } == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+    /**
+     * Guess which limit made the process fail from its terminating signal,
+     * or from the signal a shell reports through its exit code (128 + signal).
+     *
+     * Exceeding the memory limit makes allocations fail,
+     * which mostly ends up in an aborted or segfaulted process.
+     * As any crash ends up the same way, the memory limit is only a suspect.
+     * Exceeding the open files limit only makes opening files fail,
+     * and is left to the process to report.
+     */
+    pub(super) fn get_exceeded(&self, status: &ExitStatus, usage: &Usage) -> Option<Limit> {
+        let cpu_time = (usage.user_time + usage.system_time).as_secs();
+        let signal = status
+            .signal()
+            .or(status.code().filter(|e| *e > 128).map(|e| e - 128));
+        match signal {
+            Some(libc::SIGXCPU) if self.cpu.is_some() => Some(Limit::Cpu),
+            // Killed by the kernel once the hard limit is reached.
+            Some(libc::SIGKILL) if self.cpu.is_some_and(|e| cpu_time >= e) => Some(Limit::Cpu),
+            Some(libc::SIGXFSZ) if self.file_size.is_some() => Some(Limit::FileSize),
+            Some(libc::SIGABRT) | Some(libc::SIGSEGV) | Some(libc::SIGBUS)
+                if self.memory.is_some() =>
+            {
+                Some(Limit::Memory)
+            }
+            _ => None,
+        }
+    }
+}
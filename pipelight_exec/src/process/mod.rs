@@ -3,10 +3,14 @@
 #[cfg(feature = "fd")]
 mod fd;
 mod finder;
+mod group;
+mod limits;
+mod pty;
 mod run;
 
 // Re-export
 pub use finder::Finder;
+pub use limits::{Limit, Limits};
 
 use bon::{bon, builder};
 
@@ -15,6 +19,8 @@ use sysinfo::get_current_pid;
 use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time;
 use uuid::Uuid;
 // Struct
 use crate::{Io, State};
@@ -29,6 +35,16 @@ pub struct Runner {
     background: bool,
     detach: bool,
     fs: bool,
+    timeout: Option<time::Duration>,
+    // The prefix of the outputs lines streamed to the terminal
+    stream: Option<String>,
+    // The shell program and arguments used by term(), the command comes last
+    shell: Option<(String, Vec<String>)>,
+    limits: Option<Limits>,
+    // The maximum size of the outputs kept in the struct
+    max_output: Option<usize>,
+    // Whether the outputs are a pseudo-terminal
+    pty: bool,
 }
 impl Default for Runner {
     fn default() -> Self {
@@ -38,6 +54,12 @@ impl Default for Runner {
             background: false,
             detach: false,
             fs: false,
+            timeout: None,
+            stream: None,
+            shell: None,
+            limits: None,
+            max_output: None,
+            pty: false,
         }
     }
 }
@@ -50,6 +72,12 @@ impl Runner {
             background: false,
             detach: false,
             fs: false,
+            timeout: None,
+            stream: None,
+            shell: None,
+            limits: None,
+            max_output: None,
+            pty: false,
         }
     }
 }
@@ -86,6 +114,8 @@ pub struct Process {
     pub state: State,
     pub io: Io,
     pub cwd: Option<String>,
+    // environment variables passed to the process
+    pub env: Option<HashMap<String, String>>,
 }
 impl Default for Process {
     /**
@@ -101,6 +131,7 @@ impl Default for Process {
             gid: None,
             sid: None,
             cwd: None,
+            env: None,
             io: Io {
                 uuid,
                 stdin: None,
@@ -121,6 +152,115 @@ impl Process {
         self.io.stdin = Some(stdin.to_owned());
         self
     }
+    /**
+     * Set the program to run and its arguments explicitly.
+     * The first item is the program, and the arguments are passed as is,
+     * without any shell interpretation (unless combined with `term()`).
+     *
+     * ```rust
+     * # use pipelight_exec::Process;
+     * # use miette::Report;
+     *
+     * let proc = Process::new()
+     *   .args(["ls", "-al", "my directory"])
+     *   .run()?;
+     *
+     * # Ok::<(), Report>(())
+     * ```
+     */
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let args: Vec<String> = args.into_iter().map(|e| e.as_ref().to_owned()).collect();
+        // Stored as a quoted command line that parses back into the same arguments.
+        self.io.stdin = Some(shell_words::join(args));
+        self
+    }
+    /**
+     * Add (or override) an environment variable for the process.
+     */
+    pub fn env(&mut self, key: &str, value: &str) -> &mut Self {
+        let mut env = self.env.clone().unwrap_or_default();
+        env.insert(key.to_owned(), value.to_owned());
+        self.env = Some(env);
+        self
+    }
+    /**
+     * Add (or override) multiple environment variables for the process.
+     */
+    pub fn envs(&mut self, vars: &HashMap<String, String>) -> &mut Self {
+        let mut env = self.env.clone().unwrap_or_default();
+        env.extend(vars.to_owned());
+        self.env = Some(env);
+        self
+    }
+    /**
+     * Run the process in the provided working directory.
+     */
+    pub fn cwd(&mut self, path: &str) -> &mut Self {
+        self.cwd = Some(path.to_owned());
+        self
+    }
+    /**
+     * Run the command through the provided shell instead of the default one.
+     * The command is passed as the last argument, after the shell arguments.
+     * Implies `term()`.
+     *
+     * ```rust
+     * # use pipelight_exec::Process;
+     * # use miette::Report;
+     *
+     * let proc = Process::new()
+     *   .stdin("echo $0")
+     *   .shell("bash", ["-euo", "pipefail", "-c"])
+     *   .run()?;
+     *
+     * # Ok::<(), Report>(())
+     * ```
+     */
+    pub fn shell<I, S>(&mut self, program: &str, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let args = args.into_iter().map(|e| e.as_ref().to_owned()).collect();
+        self.config.shell = Some((program.to_owned(), args));
+        self.config.term = true;
+        self
+    }
+    /**
+     * Restrict the resources the process (and its children) can consume.
+     * A process terminated for exceeding a limit has it recorded in its state.
+     */
+    pub fn limits(&mut self, limits: &Limits) -> &mut Self {
+        self.config.limits = Some(limits.to_owned());
+        self
+    }
+    /**
+     * Only keep the head and the tail of outputs bigger than the provided size (in bytes),
+     * with a marker in place of the truncated part.
+     * Combined with `fs()`, the full outputs are kept in the output files.
+     */
+    pub fn max_output(&mut self, size: usize) -> &mut Self {
+        self.config.max_output = Some(size);
+        self
+    }
+    /**
+     * Run the process in a pseudo-terminal, so that it behaves as if run interactively
+     * (colors, progress bars...).
+     * The process is started in a new session with the terminal as its controlling one.
+     *
+     * Its stdout and stderr are merged into a single transcript stored as stdout,
+     * and the stderr is left empty.
+     *
+     * Only applies to processes that are waited for (not in the background).
+     */
+    pub fn pty(&mut self) -> &mut Self {
+        self.config.pty = true;
+        self
+    }
     pub fn term(&mut self) -> &mut Self {
         self.config.term = true;
         self
@@ -154,6 +294,28 @@ impl Process {
         self.config.fs = true;
         self
     }
+    /**
+     * Tee the process outputs line by line while it runs:
+     * to the terminal with the provided prefix,
+     * and to the output files if combined with `fs()`.
+     *
+     * Only applies to processes that are waited for (not in the background).
+     */
+    pub fn stream(&mut self, prefix: &str) -> &mut Self {
+        self.config.stream = Some(prefix.to_owned());
+        self
+    }
+    /**
+     * Terminate the process if it runs longer than the provided duration.
+     * It is first gently stopped (SIGTERM),
+     * and then killed (SIGKILL) if still alive after a grace period.
+     *
+     * Only applies to processes that are waited for (not in the background).
+     */
+    pub fn timeout(&mut self, duration: &time::Duration) -> &mut Self {
+        self.config.timeout = Some(duration.to_owned());
+        self
+    }
 }
 
 impl Process {
@@ -0,0 +1,71 @@
+// File manipulation
+use std::ffi::{CStr, OsStr};
+use std::fs::{File, OpenOptions};
+use std::io::Read;
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::OpenOptionsExt;
+
+/**
+* Open a pseudo-terminal.
+* Returns the master side to read the transcript from,
+* and the slave side to hand to the child process.
+*
+* The terminal gets the size of the current one if any, or 80x24.
+*/
+pub(super) fn open_pty() -> Result<(File, File), std::io::Error> {
+    let master = unsafe { libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC) };
+    if master == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // Safety: the file descriptor has just been opened and is owned by nothing else.
+    let master = unsafe { File::from_raw_fd(master) };
+    let fd = master.as_raw_fd();
+    if unsafe { libc::grantpt(fd) } == -1 || unsafe { libc::unlockpt(fd) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let mut name = [0 as libc::c_char; 128];
+    let res = unsafe { libc::ptsname_r(fd, name.as_mut_ptr(), name.len()) };
+    if res != 0 {
+        return Err(std::io::Error::from_raw_os_error(res));
+    }
+    let name = unsafe { CStr::from_ptr(name.as_ptr()) };
+    let slave = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(OsStr::from_bytes(name.to_bytes()))?;
+
+    // Window size
+    let mut size = libc::winsize {
+        ws_row: 24,
+        ws_col: 80,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    let mut current = size;
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut current) } == 0
+        && current.ws_col > 0
+    {
+        size = current;
+    }
+    unsafe { libc::ioctl(slave.as_raw_fd(), libc::TIOCSWINSZ, &size) };
+
+    Ok((master, slave))
+}
+
+/**
+* The master side of a pseudo-terminal.
+*
+* Reading it fails (EIO) once every slave side has been closed,
+* which is the end of the transcript rather than an error.
+*/
+pub(super) struct PtyReader(pub File);
+impl Read for PtyReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+        match self.0.read(buf) {
+            Err(e) if e.raw_os_error() == Some(libc::EIO) => Ok(0),
+            res => res,
+        }
+    }
+}
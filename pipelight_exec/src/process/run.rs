@@ -1,36 +1,107 @@
 use crate::dates::Duration;
-use crate::{Io, Process, State, Status};
+use crate::{Io, Limit, Limits, Process, State, Status, Usage};
 
 // Globals
 use crate::globals::{get_shell, OUTDIR, SHELL};
+use std::env;
+// Pseudo-terminal
+use super::pty::{open_pty, PtyReader};
+// Interruption signals forwarding
+use super::group;
 
 // Unix process manipulation
-use std::os::unix::process::CommandExt;
-use std::process::{Command, Stdio};
+use rustix::process::{kill_process_group, Pid, Signal};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::{thread, time};
 
 // File manipulation
 use std::fs::{create_dir_all, File};
+use std::io::{BufRead, BufReader, Read, Write};
+// Colorize
+use colored::Colorize;
 
 // Error Handling
 use log::info;
 use miette::{IntoDiagnostic, Result};
-use pipelight_error::{PipelightError, WrapError};
+use pipelight_error::{LibError, PipelightError, WrapError};
+
+/**
+* Delay between the graceful (SIGTERM) and the forced (SIGKILL) termination
+* of a timed out process.
+*/
+const GRACE_PERIOD: time::Duration = time::Duration::from_secs(5);
+
+/**
+* Delay between two checks of a running process state.
+*/
+const POLLING_INTERVAL: time::Duration = time::Duration::from_millis(50);
 
 impl Process {
     pub fn run(&mut self) -> Result<Self, PipelightError> {
         // Generate command
         let mut cmd = match self.config.term {
-            false => self.to_command(),
+            false => self.to_command()?,
             true => {
-                let mut e = Command::new(&(*SHELL.lock().unwrap()));
-                e.arg("-c").arg(self.io.stdin.as_ref().unwrap());
+                let mut e = match &self.config.shell {
+                    Some((program, args)) => {
+                        let mut e = Command::new(program);
+                        e.args(args);
+                        e
+                    }
+                    None => {
+                        let mut e = Command::new(&(*SHELL.lock().unwrap()));
+                        e.arg("-c");
+                        e
+                    }
+                };
+                e.arg(self.io.stdin.as_ref().unwrap());
                 e
             }
         };
+        // Working directory
+        if let Some(cwd) = &self.cwd {
+            cmd.current_dir(cwd);
+        }
+        // Resource limits
+        if let Some(limits) = self.config.limits.clone() {
+            unsafe {
+                cmd.pre_exec(move || limits.apply());
+            }
+        }
         cmd.stdin(Stdio::null())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
+        // Pseudo-terminal outputs are read from its master side.
+        let pty = self.config.pty && !self.config.background && !self.config.detach;
+        let mut tee = Tee::default();
+        if pty {
+            let (master, slave) = open_pty()?;
+            cmd.stdout(slave.try_clone()?).stderr(slave);
+            // Make the terminal the controlling one of a new session,
+            // for the programs that open /dev/tty or check for a controlling terminal.
+            unsafe {
+                cmd.pre_exec(set_controlling_terminal);
+            }
+            tee.pty = Some(master);
+            if env::var_os("TERM").is_none() {
+                cmd.env("TERM", "xterm-256color");
+            }
+        }
+
+        // Environment variables
+        if let Some(env) = &self.env {
+            cmd.envs(env);
+        }
+
+        // Streamed outputs are read from pipes and copied line by line.
+        let streamed =
+            self.config.stream.is_some() && !self.config.background && !self.config.detach;
+        if streamed {
+            tee.prefix = self.config.stream.clone();
+        }
+
         // Output redirection
         match self.config.fs {
             true => {
@@ -39,12 +110,26 @@ impl Process {
 
                 let stdout_path = format!("{proc_path}/1");
                 let stderr_path = format!("{proc_path}/2");
-                cmd.stdout(File::create(stdout_path)?)
-                    .stderr(File::create(stderr_path)?);
+                let stdout = File::create(stdout_path)?;
+                let stderr = File::create(stderr_path)?;
+                match streamed || pty {
+                    true => tee.files = Some((stdout, stderr)),
+                    false => {
+                        cmd.stdout(stdout).stderr(stderr);
+                    }
+                }
             }
             false => {}
         }
 
+        // Spawn the process in its own process group,
+        // so that its children can be terminated along with it on timeout.
+        // (a pseudo-terminal session already is its own process group)
+        let grouped = self.config.timeout.is_some() && !self.config.background && !pty;
+        if grouped {
+            cmd.process_group(0);
+        }
+
         match self.config.detach {
             true => {
                 // Hard detach
@@ -69,41 +154,255 @@ impl Process {
         } else {
             let child = cmd.spawn()?;
             self.pid = Some(child.id().to_owned() as i32);
+            // Close the parent copies of the terminal slave side,
+            // so that the transcript ends with the child.
+            drop(cmd);
 
+            // Out of the terminal foreground group, the process doesn't get Ctrl-C,
+            // forward it the signals that interrupt pipelight.
+            let pgid = Pid::from_raw(child.id() as i32).unwrap();
+            if grouped || pty {
+                group::register(pgid);
+            }
             duration.start();
-            let output = child.wait_with_output()?;
+            let res = wait_with_timeout(child, self.config.timeout.as_ref(), tee);
             duration.stop();
+            group::release(pgid);
+            let (output, usage, timed_out) = res?;
             self.io = Io {
                 uuid: self.io.uuid,
                 stdin: self.io.stdin.to_owned(),
                 ..Io::from(&output)
             };
+            let status = match timed_out {
+                true => Status::TimedOut,
+                false => Status::from(&output),
+            };
             self.state = State {
                 duration: Some(duration),
-                status: Some(Status::from(&output)),
+                status: Some(status),
+                exit_code: output.status.code(),
+                signal: output.status.signal(),
+                exceeded: self
+                    .config
+                    .limits
+                    .as_ref()
+                    .and_then(|e| e.get_exceeded(&output.status, &usage)),
+                usage: Some(usage),
             };
             if self.config.fs {
-                self.io.read()?;
+                self.io.read_capped(self.config.max_output)?;
                 // self.io.clean()?;
+            } else if let Some(max) = self.config.max_output {
+                self.io.truncate(max);
             }
         }
         Ok(self.to_owned())
     }
-    fn to_command(&self) -> Command {
-        // convert stdin
-        let mut args: Vec<String> = self
-            .io
-            .stdin
-            .clone()
-            .unwrap()
-            .split(" ")
-            .map(|e| e.to_owned())
-            .collect();
-
-        // Ensure internal log dir exists
+    /**
+     * Convert stdin into a program and its arguments,
+     * honoring shell quotes and escapes but without any other shell interpretation.
+     */
+    fn to_command(&self) -> Result<Command, LibError> {
+        let stdin = self.io.stdin.clone().unwrap_or_default();
+        let mut args = shell_words::split(&stdin).map_err(|e| LibError {
+            message: format!("Couldn't parse the command {:?}: {}", stdin, e),
+            help: "Check the command quotes and escapes".to_owned(),
+        })?;
+        if args.is_empty() {
+            return Err(LibError {
+                message: "Couldn't run an empty command".to_owned(),
+                help: "Provide a command with stdin() or args()".to_owned(),
+            });
+        }
         let mut cmd = Command::new(args.remove(0));
         cmd.args(args);
-        cmd
+        Ok(cmd)
+    }
+}
+
+/**
+* Start a new session and acquire the pseudo-terminal,
+* already set as the child stdout, as its controlling terminal.
+*
+* Runs in the forked child, before exec.
+*/
+fn set_controlling_terminal() -> Result<(), std::io::Error> {
+    if unsafe { libc::setsid() } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCSCTTY, 0) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/**
+* Where to copy the process outputs while they are read.
+*/
+#[derive(Debug, Default)]
+struct Tee {
+    // The stdout and stderr managed files
+    files: Option<(File, File)>,
+    // Print lines to the terminal with this prefix
+    prefix: Option<String>,
+    // The pseudo-terminal master side, replacing both stdout and stderr
+    pty: Option<File>,
+}
+
+/**
+* Read the output line by line in a background thread,
+* copy every line to the file and terminal if any,
+* and return the whole output once the pipe is closed
+* (or nothing if copied to a file).
+*
+* Terminal line endings (CRLF) are translated when `crlf` is set.
+*/
+fn drain<R: Read + Send + 'static>(
+    reader: R,
+    mut file: Option<File>,
+    prefix: Option<String>,
+    is_stderr: bool,
+    crlf: bool,
+) -> thread::JoinHandle<Result<Vec<u8>, std::io::Error>> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut buf = vec![];
+        let mut line = vec![];
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            if crlf && line.ends_with(b"\r\n") {
+                line.truncate(line.len() - 2);
+                line.push(b'\n');
+            }
+            if let Some(file) = &mut file {
+                file.write_all(&line)?;
+            }
+            if let Some(prefix) = &prefix {
+                let text = String::from_utf8_lossy(&line);
+                let text = format!("{} {}", format!("{} |", prefix).dimmed(), text.trim_end());
+                match is_stderr {
+                    true => eprintln!("{}", text),
+                    false => println!("{}", text),
+                }
+            }
+            // Outputs copied to a file are read back from it.
+            match file {
+                Some(_) => line.clear(),
+                None => buf.append(&mut line),
+            }
+        }
+        Ok(buf)
+    })
+}
+
+/**
+* Wait for the child process to exit and collect its outputs.
+* If the child runs longer than the provided timeout,
+* its process group is gently stopped (SIGTERM),
+* and then killed (SIGKILL) after a grace period.
+*
+* Outputs are copied to the tee destinations as soon as they are read.
+*
+* Returns the process outputs, its resource usage and whether the process has timed out.
+*/
+fn wait_with_timeout(
+    mut child: Child,
+    timeout: Option<&time::Duration>,
+    tee: Tee,
+) -> Result<(Output, Usage, bool), std::io::Error> {
+    // Drain piped outputs in the background
+    // to prevent the child from blocking on a full pipe.
+    let (stdout_file, stderr_file) = match tee.files {
+        Some((stdout, stderr)) => (Some(stdout), Some(stderr)),
+        None => (None, None),
+    };
+    let stdout = match tee.pty {
+        // The terminal merges stdout and stderr into a single transcript.
+        Some(master) => {
+            let reader = PtyReader(master);
+            Some(drain(reader, stdout_file, tee.prefix.clone(), false, true))
+        }
+        None => child
+            .stdout
+            .take()
+            .map(|e| drain(e, stdout_file, tee.prefix.clone(), false, false)),
+    };
+    let stderr = child
+        .stderr
+        .take()
+        .map(|e| drain(e, stderr_file, tee.prefix.clone(), true, false));
+
+    let pgid = Pid::from_raw(child.id() as i32).unwrap();
+    let started_at = time::Instant::now();
+    let mut timed_out = false;
+    let mut terminated_at: Option<time::Instant> = None;
+
+    let (status, usage) = loop {
+        // Block until exit when there is no time budget to enforce.
+        if let Some(res) = wait4(child.id() as i32, timeout.is_none())? {
+            break res;
+        }
+        match terminated_at {
+            None => {
+                if timeout.is_some_and(|e| started_at.elapsed() >= *e) {
+                    info!("process timed out, sending SIGTERM to group {:?}", pgid);
+                    timed_out = true;
+                    terminated_at = Some(time::Instant::now());
+                    let _ = kill_process_group(pgid, Signal::Term);
+                }
+            }
+            Some(terminated_at) => {
+                if terminated_at.elapsed() >= GRACE_PERIOD {
+                    info!("process still alive, sending SIGKILL to group {:?}", pgid);
+                    let _ = kill_process_group(pgid, Signal::Kill);
+                    if let Some(res) = wait4(child.id() as i32, true)? {
+                        break res;
+                    }
+                }
+            }
+        }
+        thread::sleep(POLLING_INTERVAL);
+    };
+
+    let stdout = match stdout {
+        Some(handle) => handle.join().unwrap()?,
+        None => vec![],
+    };
+    let stderr = match stderr {
+        Some(handle) => handle.join().unwrap()?,
+        None => vec![],
+    };
+    let output = Output {
+        status,
+        stdout,
+        stderr,
+    };
+    Ok((output, usage, timed_out))
+}
+
+/**
+* Reap the child process and collect its resource usage.
+* Returns None if the process is still running and `block` is false.
+*/
+fn wait4(pid: i32, block: bool) -> Result<Option<(ExitStatus, Usage)>, std::io::Error> {
+    let options = match block {
+        true => 0,
+        false => libc::WNOHANG,
+    };
+    let mut status = 0;
+    // Safety: rusage is a plain C struct for which zeroes are a valid value.
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        match unsafe { libc::wait4(pid, &mut status, options, &mut rusage) } {
+            -1 => {
+                let err = std::io::Error::last_os_error();
+                if err.kind() != std::io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+            0 => return Ok(None),
+            _ => return Ok(Some((ExitStatus::from_raw(status), Usage::from(&rusage)))),
+        }
     }
 }
 
@@ -120,6 +419,131 @@ mod test {
         Ok(())
     }
     #[test]
+    fn quoted_args() -> Result<()> {
+        let proc = Process::new().stdin("printf %s| 'a  b' \"c\\\"d\"").run()?;
+        assert_eq!(proc.io.stdout, Some("a  b|c\"d|".to_owned()));
+        Ok(())
+    }
+    #[test]
+    fn args() -> Result<()> {
+        let proc = Process::new()
+            .args(["printf", "%s|", "a  b", "$HOME"])
+            .run()?;
+        assert_eq!(
+            proc.io.stdin,
+            Some("printf '%s|' 'a  b' '$HOME'".to_owned())
+        );
+        assert_eq!(proc.io.stdout, Some("a  b|$HOME|".to_owned()));
+        Ok(())
+    }
+    #[test]
+    fn cwd() -> Result<()> {
+        let proc = Process::new().stdin("pwd").cwd("/tmp").run()?;
+        assert_eq!(proc.io.stdout, Some("/tmp".to_owned()));
+        Ok(())
+    }
+    #[test]
+    fn shell() -> Result<()> {
+        let proc = Process::new()
+            .stdin("false; echo unreachable")
+            .shell("sh", ["-e", "-c"])
+            .run()?;
+        assert_eq!(proc.io.stdout, None);
+        assert_eq!(proc.state.status, Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn usage() -> Result<()> {
+        let proc = Process::new().stdin("head -c 10000000 /dev/zero").run()?;
+        let usage = proc.state.usage.unwrap();
+        assert!(usage.max_rss > 0);
+        Ok(())
+    }
+    #[test]
+    fn cpu_limit() -> Result<()> {
+        let limits = Limits {
+            cpu: Some(1),
+            ..Limits::default()
+        };
+        let proc = Process::new()
+            .stdin("while :; do :; done")
+            .term()
+            .limits(&limits)
+            .run()?;
+        assert_eq!(proc.state.status, Some(Status::Failed));
+        assert_eq!(proc.state.exceeded, Some(Limit::Cpu));
+        Ok(())
+    }
+    #[test]
+    fn file_size_limit() -> Result<()> {
+        let limits = Limits {
+            file_size: Some(1024),
+            ..Limits::default()
+        };
+        let test_dir = format!(
+            "{}/test_dir_tmp/file_size_limit",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        std::fs::create_dir_all(&test_dir).into_diagnostic()?;
+        let proc = Process::new()
+            .stdin(&format!("head -c 4096 /dev/zero > {}/file", test_dir))
+            .term()
+            .limits(&limits)
+            .run()?;
+        std::fs::remove_dir_all(&test_dir).into_diagnostic()?;
+        assert_eq!(proc.state.exceeded, Some(Limit::FileSize));
+        Ok(())
+    }
+    #[test]
+    fn binary_output() -> Result<()> {
+        let proc = Process::new().stdin("printf 'a\\377\\000b'").term().run()?;
+        assert_eq!(proc.io.stdout, Some("a\u{FFFD}\0b".to_owned()));
+        assert_eq!(proc.io.is_binary, Some(true));
+        Ok(())
+    }
+    #[test]
+    fn max_output() -> Result<()> {
+        let proc = Process::new()
+            .stdin("seq 1 10000")
+            .term()
+            .fs()
+            .max_output(100)
+            .run()?;
+        let stdout = proc.io.stdout.unwrap();
+        assert!(stdout.starts_with("1\n2\n"));
+        assert!(stdout.contains("bytes truncated"));
+        assert!(stdout.ends_with("10000\n"));
+        Ok(())
+    }
+    #[test]
+    fn pty() -> Result<()> {
+        let proc = Process::new()
+            .stdin("test -t 1 && echo tty; echo err >&2")
+            .term()
+            .fs()
+            .pty()
+            .run()?;
+        assert_eq!(proc.io.stdout, Some("tty\nerr\n".to_owned()));
+        assert_eq!(proc.state.status, Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn pty_controlling_terminal() -> Result<()> {
+        let proc = Process::new()
+            .stdin("true < /dev/tty && echo ctty")
+            .term()
+            .fs()
+            .pty()
+            .run()?;
+        assert_eq!(proc.io.stdout, Some("ctty\n".to_owned()));
+        Ok(())
+    }
+    #[test]
+    fn unterminated_quote() {
+        let res = Process::new().stdin("echo 'test").run();
+        assert!(res.is_err());
+    }
+    #[test]
     fn default_wait_for_output() -> Result<()> {
         let proc = Process::new().stdin("sleep 3").run()?;
         println!("{:#?}", proc);
@@ -135,12 +559,56 @@ mod test {
         Ok(())
     }
     #[test]
+    fn env() -> Result<()> {
+        let proc = Process::new()
+            .stdin("echo $TEST_VAR")
+            .term()
+            .env("TEST_VAR", "test")
+            .run()?;
+        assert_eq!(proc.io.stdout, Some("test".to_owned()));
+        Ok(())
+    }
+    #[test]
+    fn timeout() -> Result<()> {
+        let proc = Process::new()
+            .stdin("sleep 30")
+            .term()
+            .timeout(&time::Duration::from_secs(1))
+            .run()?;
+        assert_eq!(proc.state.status, Some(Status::TimedOut));
+        Ok(())
+    }
+    #[test]
+    fn exit_code() -> Result<()> {
+        let proc = Process::new().stdin("exit 3").term().run()?;
+        assert_eq!(proc.state.status, Some(Status::Failed));
+        assert_eq!(proc.state.exit_code, Some(3));
+        assert_eq!(proc.state.signal, None);
+
+        let proc = Process::new().stdin("kill -9 $$").term().run()?;
+        assert_eq!(proc.state.exit_code, None);
+        assert_eq!(proc.state.signal, Some(9));
+        Ok(())
+    }
+    #[test]
     fn fs() -> Result<()> {
         let proc = Process::new().stdin("echo test").fs().run()?;
         assert_eq!(proc.io.stdout, Some("test\n".to_owned()));
         Ok(())
     }
     #[test]
+    fn stream() -> Result<()> {
+        let proc = Process::new()
+            .stdin("echo test; echo error >&2")
+            .term()
+            .fs()
+            .stream("test")
+            .run()?;
+        assert_eq!(proc.io.stdout, Some("test\n".to_owned()));
+        assert_eq!(proc.io.stderr, Some("error\n".to_owned()));
+        Ok(())
+    }
+    #[test]
     fn background() -> Result<()> {
         let proc = Process::new().stdin("sleep 3").background().run()?;
         assert_eq!(proc.io.stdout, None);
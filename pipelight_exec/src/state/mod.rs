@@ -1,4 +1,7 @@
 pub mod statuable;
+mod usage;
+
+pub use usage::Usage;
 
 use serde::{Deserialize, Serialize};
 // Colors and Formatting
@@ -11,6 +14,7 @@ use std::process::Output;
 use convert_case::{Case, Casing};
 // Logger
 use crate::dates::Duration;
+use crate::Limit;
 use log::warn;
 
 /**
@@ -20,6 +24,14 @@ use log::warn;
 pub struct State {
     pub duration: Option<Duration>,
     pub status: Option<Status>,
+    // The process exit code if it exited normally.
+    pub exit_code: Option<i32>,
+    // The signal that terminated the process if any.
+    pub signal: Option<i32>,
+    // The resources consumed by the process.
+    pub usage: Option<Usage>,
+    // The resource limit the process has been terminated for if any.
+    pub exceeded: Option<Limit>,
 }
 
 /**
@@ -39,6 +51,10 @@ pub enum Status {
     Running,
     // The process has been abruptly halted.
     Aborted,
+    // The process has exceeded its time budget and has been terminated.
+    TimedOut,
+    // The process has not been run because its condition was not met.
+    Skipped,
 }
 
 impl From<&String> for Status {
@@ -50,6 +66,8 @@ impl From<&String> for Status {
             "failed" => Status::Failed,
             "running" => Status::Running,
             "aborted" => Status::Aborted,
+            "timed_out" => Status::TimedOut,
+            "skipped" => Status::Skipped,
             _ => {
                 warn!("unexpected string, assuminng default state");
                 Status::default()
@@ -65,6 +83,8 @@ impl From<&Status> for String {
             Status::Failed => "failed".to_owned(),
             Status::Running => "running".to_owned(),
             Status::Aborted => "aborted".to_owned(),
+            Status::TimedOut => "timed-out".to_owned(),
+            Status::Skipped => "skipped".to_owned(),
         }
     }
 }
@@ -89,6 +109,8 @@ impl fmt::Display for Status {
             Status::Failed => write!(f, "{} {}", icon.red(), "Failed".normal().bold()),
             Status::Running => write!(f, "{} {}", icon.green(), "Running".bold()),
             Status::Aborted => write!(f, "{} {}", icon.yellow(), "Aborted".bold()),
+            Status::TimedOut => write!(f, "{} {}", icon.magenta(), "TimedOut".bold()),
+            Status::Skipped => write!(f, "{} {}", icon.white(), "Skipped".bold()),
         }
     }
 }
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time;
+
+/**
+* The resources consumed by a process and its waited-for children,
+* as reported by the kernel once the process has been reaped.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Usage {
+    // Time spent executing user instructions.
+    pub user_time: time::Duration,
+    // Time spent in the kernel on behalf of the process.
+    pub system_time: time::Duration,
+    // Peak resident set size in kilobytes.
+    pub max_rss: u64,
+    // Number of filesystem blocks read.
+    pub block_input: u64,
+    // Number of filesystem blocks written.
+    pub block_output: u64,
+}
+
+impl From<&libc::rusage> for Usage {
+    fn from(e: &libc::rusage) -> Usage {
+        let to_duration =
+            |tv: &libc::timeval| time::Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000);
+        Usage {
+            user_time: to_duration(&e.ru_utime),
+            system_time: to_duration(&e.ru_stime),
+            max_rss: e.ru_maxrss as u64,
+            block_input: e.ru_inblock as u64,
+            block_output: e.ru_oublock as u64,
+        }
+    }
+}
+
+impl Usage {
+    /**
+     * Aggregate the usage of processes run one after the other or concurrently.
+     * Times and I/O are summed, and the memory peak is the highest one.
+     *
+     * Returns None if there is nothing to aggregate.
+     */
+    pub fn aggregate<'a, I>(usages: I) -> Option<Usage>
+    where
+        I: IntoIterator<Item = &'a Usage>,
+    {
+        usages.into_iter().fold(None, |acc, e| {
+            let mut acc = acc.unwrap_or_default();
+            acc.user_time += e.user_time;
+            acc.system_time += e.system_time;
+            acc.max_rss = acc.max_rss.max(e.max_rss);
+            acc.block_input += e.block_input;
+            acc.block_output += e.block_output;
+            Some(acc)
+        })
+    }
+}
+
+/**
+* Displays a compact one line summary.
+*/
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cpu: {:.2}s user, {:.2}s system - max rss: {:.1} MiB - blocks: {} in, {} out",
+            self.user_time.as_secs_f64(),
+            self.system_time.as_secs_f64(),
+            self.max_rss as f64 / 1024.0,
+            self.block_input,
+            self.block_output
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aggregate() {
+        let a = Usage {
+            user_time: time::Duration::from_millis(200),
+            max_rss: 1024,
+            block_output: 8,
+            ..Usage::default()
+        };
+        let b = Usage {
+            user_time: time::Duration::from_millis(300),
+            max_rss: 4096,
+            block_output: 2,
+            ..Usage::default()
+        };
+        let res = Usage::aggregate([&a, &b]).unwrap();
+        assert_eq!(res.user_time, time::Duration::from_millis(500));
+        assert_eq!(res.max_rss, 4096);
+        assert_eq!(res.block_output, 10);
+        assert_eq!(Usage::aggregate([]), None);
+    }
+}
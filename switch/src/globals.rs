@@ -54,8 +54,8 @@ pub fn hydrate_trigger() -> Result<()> {
             DetachableCommands::Trigger(trigger) => {
                 flag = trigger.flag;
             }
-            DetachableCommands::Run(pipeline) => {
-                flag = pipeline.trigger.flag;
+            DetachableCommands::Run(run) => {
+                flag = run.pipeline.trigger.flag;
             }
             _ => {}
         }
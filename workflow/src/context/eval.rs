@@ -0,0 +1,59 @@
+// Structs
+use super::Context;
+use cast::Expr;
+
+/**
+The value an expression evaluates to.
+*/
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Value {
+    Str(String),
+    Bool(bool),
+    Null,
+}
+impl Value {
+    /**
+    Null, false and empty strings are falsy.
+    */
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Str(string) => !string.is_empty(),
+            Value::Bool(boolean) => *boolean,
+            Value::Null => false,
+        }
+    }
+    fn is_equal(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Null, _) | (_, Value::Null) => false,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Str(a), Value::Bool(b)) | (Value::Bool(b), Value::Str(a)) => {
+                *a == b.to_string()
+            }
+        }
+    }
+}
+
+impl Context {
+    /**
+    Evaluate an expression against the context variables.
+    Unresolved variables evaluate to null.
+    */
+    pub fn eval(&self, expr: &Expr) -> Value {
+        match expr {
+            Expr::Str(string) => Value::Str(string.to_owned()),
+            Expr::Bool(boolean) => Value::Bool(*boolean),
+            Expr::Null => Value::Null,
+            Expr::Var(path) => match self.get(path) {
+                Some(value) => Value::Str(value),
+                None => Value::Null,
+            },
+            Expr::Not(e) => Value::Bool(!self.eval(e).is_truthy()),
+            Expr::Eq(a, b) => Value::Bool(self.eval(a).is_equal(&self.eval(b))),
+            Expr::Ne(a, b) => Value::Bool(!self.eval(a).is_equal(&self.eval(b))),
+            Expr::And(a, b) => Value::Bool(self.eval(a).is_truthy() && self.eval(b).is_truthy()),
+            Expr::Or(a, b) => Value::Bool(self.eval(a).is_truthy() || self.eval(b).is_truthy()),
+        }
+    }
+}
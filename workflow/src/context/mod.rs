@@ -0,0 +1,142 @@
+// Structs
+use crate::types::{Pipeline, Step, StepOrParallel, Trigger};
+use std::collections::HashMap;
+// Interpolation
+use cast::Quoting;
+// Error Handling
+use log::warn;
+use miette::Result;
+
+mod eval;
+// Tests
+mod test;
+
+pub use eval::Value;
+
+/**
+The variables step conditions are evaluated against
+and command placeholders are replaced with.
+
+They are resolved from the triggering environment and the pipeline state:
+- git.branch, git.tag, git.commit, git.action (also available as trigger.<same>)
+- pipeline.name
+- env.<NAME>, the step environment and then the process environment.
+- steps.<name>.status, the status of the steps that have been run.
+- steps.<name>.outputs.<key>, the outputs of the steps that have been run.
+*/
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Context {
+    pub variables: HashMap<String, String>,
+}
+
+impl Context {
+    pub fn new(pipeline: &Pipeline) -> Result<Context> {
+        let mut context = Context::default();
+
+        // Trigger
+        let trigger = match &pipeline.event {
+            Some(event) => event.trigger.clone(),
+            None => Trigger::get()?,
+        };
+        let git_ref = trigger.get_ref()?;
+        let git_ref_key = match trigger {
+            Trigger::TriggerBranch(_) => "branch",
+            Trigger::TriggerTag(_) => "tag",
+        };
+        let action = trigger.get_action()?.map(|e| String::from(&e));
+        for prefix in ["git", "trigger"] {
+            context.insert(&format!("{}.{}", prefix, git_ref_key), git_ref.clone());
+            context.insert(&format!("{}.commit", prefix), trigger.get_commit()?);
+            context.insert(&format!("{}.action", prefix), action.clone());
+        }
+
+        // Pipeline
+        context.insert("pipeline.name", Some(pipeline.name.clone()));
+
+        // Steps
+        for step in &pipeline.steps {
+            let steps: Vec<&Step> = match step {
+                StepOrParallel::Step(res) => vec![res],
+                StepOrParallel::Parallel(res) => res.steps.iter().collect(),
+            };
+            for step in steps {
+                let key = format!("steps.{}.status", step.name);
+                context.insert(&key, step.status.as_ref().map(String::from));
+                for (name, output) in step.outputs.iter().flatten() {
+                    let key = format!("steps.{}.outputs.{}", step.name, name);
+                    context.insert(&key, output.value.clone());
+                }
+            }
+        }
+        Ok(context)
+    }
+    /**
+    Add environment variables to the context.
+    */
+    pub fn env(&mut self, env: &HashMap<String, String>) -> &mut Self {
+        for (key, value) in env {
+            self.insert(&format!("env.{}", key), Some(value.to_owned()));
+        }
+        self
+    }
+    fn insert(&mut self, key: &str, value: Option<String>) {
+        if let Some(value) = value {
+            self.variables.insert(key.to_owned(), value);
+        }
+    }
+    /**
+    Resolve a variable path.
+    Environment variables fall back to the pipelight process environment.
+    */
+    pub fn get(&self, path: &str) -> Option<String> {
+        if let Some(value) = self.variables.get(path) {
+            return Some(value.to_owned());
+        }
+        if let Some(name) = path.strip_prefix("env.") {
+            return std::env::var(name).ok();
+        }
+        None
+    }
+    /**
+    Replace the `${{ <path> }}` placeholders of a command with the variables values.
+    Values are shell-quoted so that they are passed as a single word
+    and can't inject commands (ex: a branch named `main;reboot`).
+
+    A placeholder already written in quotes (ex: `echo "${{ git.branch }}"`)
+    is only escaped, so that the command quotes are kept as is.
+    Placeholders without a value are replaced with an empty string.
+    */
+    pub fn interpolate(&self, text: &str) -> String {
+        cast::interpolate(text, |path, quoting| match self.get(path) {
+            Some(value) => Some(quote(&value, quoting)),
+            None => {
+                warn!(
+                    "No value for the placeholder ${{{{ {} }}}}, replaced with an empty string",
+                    path
+                );
+                None
+            }
+        })
+    }
+}
+
+/**
+Quote a value to be read literally by the shell,
+inside the quotes its placeholder is written in.
+*/
+fn quote(value: &str, quoting: Quoting) -> String {
+    match quoting {
+        Quoting::None => shell_words::quote(value).into_owned(),
+        Quoting::Single => value.replace('\'', r"'\''"),
+        Quoting::Double => {
+            let mut res = String::new();
+            for c in value.chars() {
+                if matches!(c, '\\' | '"' | '$' | '`') {
+                    res.push('\\');
+                }
+                res.push(c);
+            }
+            res
+        }
+    }
+}
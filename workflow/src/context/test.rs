@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod context {
+    use crate::context::Context;
+    use cast::Expr;
+    use std::collections::HashMap;
+
+    fn context() -> Context {
+        Context {
+            variables: HashMap::from([
+                ("trigger.branch".to_owned(), "main".to_owned()),
+                ("trigger.action".to_owned(), "pre-push".to_owned()),
+                ("steps.build.status".to_owned(), "succeeded".to_owned()),
+            ]),
+        }
+    }
+    fn eval(expr: &str) -> bool {
+        context().eval(&Expr::parse(expr).unwrap()).is_truthy()
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(eval("trigger.branch == 'main'"));
+        assert!(eval("trigger.action != 'manual'"));
+        assert!(eval(
+            "steps.build.status == 'succeeded' && !(trigger.branch == 'dev')"
+        ));
+        assert!(eval(
+            "trigger.branch == 'dev' || trigger.action == 'pre-push'"
+        ));
+    }
+    #[test]
+    fn unresolved() {
+        assert!(eval("trigger.tag == null"));
+        assert!(!eval("steps.lint.status"));
+        assert!(eval("!env.PIPELIGHT_UNDEFINED_VAR"));
+    }
+    #[test]
+    fn env() {
+        let mut context = context();
+        context.env(&HashMap::from([("CI".to_owned(), "true".to_owned())]));
+        let expr = Expr::parse("env.CI == true").unwrap();
+        assert!(context.eval(&expr).is_truthy());
+    }
+    #[test]
+    fn interpolate() {
+        let text = "echo ${{ trigger.branch }} ${{steps.build.status}} ${{ git.tag }}";
+        assert_eq!(context().interpolate(text), "echo main succeeded ");
+    }
+    #[test]
+    fn interpolate_quoted() {
+        let mut context = context();
+        context.variables.insert(
+            "trigger.branch".to_owned(),
+            "dev;rm -rf $HOME`id`".to_owned(),
+        );
+        let text = "echo ${{ trigger.branch }}";
+        assert_eq!(context.interpolate(text), "echo 'dev;rm -rf $HOME`id`'");
+    }
+    #[test]
+    fn interpolate_in_quotes() {
+        let mut context = context();
+        let text = r#"echo "branch: ${{ trigger.branch }}" 'branch: ${{ trigger.branch }}'"#;
+        assert_eq!(
+            context.interpolate(text),
+            r#"echo "branch: main" 'branch: main'"#
+        );
+        // Values are escaped inside the quotes
+        context.variables.insert(
+            "trigger.branch".to_owned(),
+            r#"it's "$HOME"`id`"#.to_owned(),
+        );
+        assert_eq!(
+            context.interpolate(text),
+            r#"echo "branch: it's \"\$HOME\"\`id\`" 'branch: it'\''s "$HOME"`id`'"#
+        );
+    }
+}
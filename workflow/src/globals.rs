@@ -2,7 +2,10 @@
 use once_cell::sync::Lazy;
 use std::sync::{Arc, Mutex};
 // Struct
+use crate::logs::store::LogEntry;
 use crate::types::{Config, Pipeline, Trigger};
+use std::collections::HashMap;
+use uuid::Uuid;
 
 /**
 Here we use global variables
@@ -13,3 +16,17 @@ pub static CONFIG: Lazy<Arc<Mutex<Config>>> = Lazy::new(|| Arc::new(Mutex::new(C
 pub static TRIGGER_ENV: Lazy<Arc<Mutex<Trigger>>> =
     Lazy::new(|| Arc::new(Mutex::new(Trigger::default())));
 pub static LOGS: Lazy<Arc<Mutex<Option<Vec<Pipeline>>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+
+type Indexed = HashMap<(String, Uuid), LogEntry>;
+
+/**
+The last metadata record appended to the logs index for every run saved by this process,
+by logs directory and run uuid.
+*/
+pub static INDEXED: Lazy<Arc<Mutex<Indexed>>> = Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+/**
+Whether the commands outputs are streamed to the terminal while the pipeline runs.
+Only relevant when the pipeline is attached to the standard I/O.
+*/
+pub static STREAM: Lazy<Arc<Mutex<bool>>> = Lazy::new(|| Arc::new(Mutex::new(false)));
@@ -6,6 +6,7 @@
 pub mod error;
 // Methods
 mod config;
+pub mod context;
 pub mod globals;
 mod logs;
 pub mod pipeline;
@@ -17,6 +18,8 @@ pub mod types;
 
 // Re-export
 pub use error::*;
+pub use logs::stats::{PipelineStats, Stats, StepStats};
+pub use logs::store::{FileStore, LogEntry, LogStore};
 pub use pipelight_exec::Statuable;
 pub use traits::Getters;
 pub use types::*;
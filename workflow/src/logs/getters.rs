@@ -1,10 +1,14 @@
 // Structs
-use crate::pipeline::Filters;
+use crate::logs::store::{FileStore, LogEntry};
+use crate::pipeline::{Filters, Query};
 use crate::types::{Logs, Pipeline};
 // Trait
+use crate::logs::store::LogStore;
 use crate::traits::Getters;
 // Error Handling
-use miette::{Error, IntoDiagnostic, Result};
+use log::warn;
+use miette::{Error, Result};
+use uuid::Uuid;
 // Global vars
 use crate::globals::LOGS;
 
@@ -17,12 +21,7 @@ impl Logs {
         // Get global
         if LOGS.lock().unwrap().clone().is_none() {
             // Read log files
-            let json_logs: Vec<String> = cast::Logs::read(".pipelight/logs")?;
-            let mut pipelines: Vec<Pipeline> = vec![];
-            for json in json_logs {
-                let pipeline = serde_json::from_str::<Pipeline>(&json).into_diagnostic()?;
-                pipelines.push(pipeline);
-            }
+            let mut pipelines: Vec<Pipeline> = FileStore::default().load_all()?;
             pipelines = Filters::sort_by_date_asc(pipelines)?;
             // Set global
             *LOGS.lock().unwrap() = Some(pipelines);
@@ -45,31 +44,19 @@ impl Getters<Pipeline> for Logs {
         }
     }
     fn get_by_name(name: &str) -> Result<Pipeline> {
-        let mut pipelines = Logs::get()?;
-        pipelines = pipelines
-            .iter()
-            .filter(|p| p.name == name)
-            .cloned()
-            .collect::<Vec<Pipeline>>();
-        match pipelines.pop() {
-            None => {
-                let message = format!("Couldn't find a pipeline named {:?}, in logs", name);
-                Err(Error::msg(message))
-            }
-            Some(p) => Ok(p),
-        }
+        let entry = Logs::get_last_entry_by_name(name)?;
+        Logs::get_by_uuid(&entry.uuid)
     }
 }
 
 // More getters
 impl Logs {
     pub fn get_many_by_name(name: &str) -> Result<Vec<Pipeline>> {
-        let mut pipelines = Logs::get()?;
-        pipelines = pipelines
-            .iter()
-            .filter(|p| p.name == *name)
-            .cloned()
-            .collect::<Vec<Pipeline>>();
+        let entries: Vec<LogEntry> = Logs::get_entries()?
+            .into_iter()
+            .filter(|e| e.name == name)
+            .collect();
+        let pipelines = Logs::load(&entries)?;
         match pipelines.is_empty() {
             false => Ok(pipelines),
             true => {
@@ -78,4 +65,70 @@ impl Logs {
             }
         }
     }
+    /**
+    Return the metadata of every run from the logs index,
+    without reading the runs full logs.
+    Sorted by ascending date.
+    */
+    pub fn get_entries() -> Result<Vec<LogEntry>> {
+        let mut entries = FileStore::default().entries()?;
+        entries.sort_by_key(|e| e.get_date());
+        Ok(entries)
+    }
+    /**
+    Return the metadata of the last run of the named pipeline.
+    */
+    pub fn get_last_entry_by_name(name: &str) -> Result<LogEntry> {
+        let entries = Logs::get_entries()?;
+        match entries.into_iter().rev().find(|e| e.name == name) {
+            Some(e) => Ok(e),
+            None => {
+                let message = format!("Couldn't find a pipeline named {:?}, in logs", name);
+                Err(Error::msg(message))
+            }
+        }
+    }
+    /**
+    Return the pipeline run with the given uuid.
+    */
+    pub fn get_by_uuid(uuid: &Uuid) -> Result<Pipeline> {
+        match FileStore::default().load(uuid) {
+            Ok(mut pipeline) => {
+                pipeline.sanitize()?;
+                Ok(pipeline)
+            }
+            Err(_err) => {
+                let message = format!("Couldn't find a pipeline run with uuid {}, in logs", uuid);
+                Err(Error::msg(message))
+            }
+        }
+    }
+    /**
+    Return the pipeline runs that match the query, sorted by ascending date.
+    The runs are filtered on the logs index,
+    so that only the matching runs full logs are read.
+    */
+    pub fn query(query: &Query) -> Result<Vec<Pipeline>> {
+        let entries = Filters::query(Logs::get_entries()?, query)?;
+        Logs::load(&entries)
+    }
+    /**
+    Return the pipeline runs of the given index entries,
+    reading only their own log files.
+    */
+    pub fn load(entries: &[LogEntry]) -> Result<Vec<Pipeline>> {
+        let store = FileStore::default();
+        let mut pipelines = vec![];
+        for entry in entries {
+            match store.load(&entry.uuid) {
+                Ok(pipeline) => pipelines.push(pipeline),
+                Err(_err) => warn!("Stripped corrupted log of run: {}", entry.uuid),
+            }
+        }
+        let logs = Logs {
+            pipelines: Some(pipelines),
+        }
+        .sanitize()?;
+        Ok(logs.pipelines.unwrap_or_default())
+    }
 }
@@ -1,8 +1,12 @@
 // Struct
-use crate::types::Logs;
+use crate::types::{Logs, Pipeline};
+use store::FileStore;
 // Getters
 use crate::Getters;
 mod getters;
+mod prune;
+pub mod stats;
+pub mod store;
 // Tests
 mod test;
 // Traits
@@ -11,8 +15,6 @@ use pipelight_exec::{Statuable, Status};
 use rayon::prelude::*;
 // Error Handling
 use miette::Result;
-// Global vars
-use crate::globals::LOGS;
 
 impl Logs {
     /**
@@ -20,15 +22,10 @@ impl Logs {
     */
     pub fn sanitize(&mut self) -> Result<Self> {
         if let Some(mut pipelines) = self.pipelines.clone() {
-            pipelines.par_iter_mut().for_each(|pipeline| {
-                if pipeline.get_status() == Some(Status::Running) && !pipeline.is_running().unwrap()
-                {
-                    pipeline.set_status(Some(Status::Aborted));
-                    pipeline.log().unwrap();
-                }
-            });
-            *LOGS.lock().unwrap() = Some(pipelines);
-            self.pipelines = LOGS.lock().unwrap().clone();
+            pipelines
+                .par_iter_mut()
+                .for_each(|pipeline| pipeline.sanitize().unwrap());
+            self.pipelines = Some(pipelines);
         }
         Ok(self.to_owned())
     }
@@ -43,6 +40,21 @@ impl Logs {
                 pipeline.clean()?;
             }
         }
+        FileStore::default().compact()?;
+        Ok(())
+    }
+}
+
+impl Pipeline {
+    /**
+    Report the run as aborted if it is logged as running
+    while its process is gone.
+    */
+    pub fn sanitize(&mut self) -> Result<()> {
+        if self.get_status() == Some(Status::Running) && !self.is_running()? {
+            self.set_status(Some(Status::Aborted));
+            self.log()?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,244 @@
+// Structs
+use super::store::{FileStore, LogEntry, LogStore};
+use crate::types::{Config, Logs, LogsOpts};
+use std::collections::HashMap;
+// Dates
+use chrono::Local;
+use pipelight_exec::dates::convert::iso8601_to_std_duration;
+// Filesystem
+use std::fs;
+// Error Handling
+use log::{trace, warn};
+use miette::Result;
+// Global vars
+use crate::globals::LOGS;
+
+impl Logs {
+    /**
+    Return the runs that fall out of the retention policy, oldest first.
+    - runs beyond the `keep` most recent ones of a pipeline,
+    - runs older than `max_age`,
+    - the oldest runs, until the logs total size fits into `max_size`.
+
+    Running pipelines are never pruned.
+    */
+    pub fn get_prunable(opts: &LogsOpts) -> Result<Vec<LogEntry>> {
+        Logs::get_prunable_from(&FileStore::default(), opts)
+    }
+    fn get_prunable_from(store: &FileStore, opts: &LogsOpts) -> Result<Vec<LogEntry>> {
+        let mut entries = store.entries()?;
+        entries.sort_by_key(|e| e.get_date());
+        let max_age = match &opts.max_age {
+            Some(max_age) => Some(iso8601_to_std_duration(max_age)?),
+            None => None,
+        };
+        let now = Local::now();
+
+        let mut kept: Vec<LogEntry> = vec![];
+        let mut pruned: Vec<LogEntry> = vec![];
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        // Most recent runs first
+        for entry in entries.into_iter().rev() {
+            if entry.is_running() {
+                continue;
+            }
+            let count = counts.entry(entry.name.clone()).or_default();
+            *count += 1;
+            let is_extra = opts.keep.is_some_and(|keep| *count > keep);
+            let is_old = match (max_age, entry.get_date()) {
+                (Some(max_age), Some(date)) => (now - date).to_std().is_ok_and(|e| e > max_age),
+                _ => false,
+            };
+            if is_extra || is_old {
+                pruned.push(entry);
+            } else {
+                kept.push(entry);
+            }
+        }
+        // Drop the oldest runs until the logs fit
+        if let Some(max_size) = opts.max_size {
+            let sizes = kept
+                .iter()
+                .map(|e| Logs::get_run_size(store, e))
+                .collect::<Vec<u64>>();
+            let mut total: u64 = sizes.iter().sum();
+            while total > max_size {
+                match (kept.pop(), sizes.get(kept.len())) {
+                    (Some(entry), Some(size)) => {
+                        total -= size;
+                        pruned.push(entry);
+                    }
+                    _ => break,
+                }
+            }
+        }
+        pruned.sort_by_key(|e| e.get_date());
+        Ok(pruned)
+    }
+    /**
+    Delete the runs that fall out of the retention policy,
+    along with their commands outputs.
+    Return the deleted runs, or the ones that would be deleted on a dry run.
+    */
+    pub fn prune(opts: &LogsOpts, dry_run: bool) -> Result<Vec<LogEntry>> {
+        Logs::prune_from(&FileStore::default(), opts, dry_run)
+    }
+    fn prune_from(store: &FileStore, opts: &LogsOpts, dry_run: bool) -> Result<Vec<LogEntry>> {
+        let pruned = Logs::get_prunable_from(store, opts)?;
+        if dry_run || pruned.is_empty() {
+            return Ok(pruned);
+        }
+        for entry in &pruned {
+            let message = format!("Pruning logs of run: {}", entry.uuid);
+            trace!("{}", message);
+            // Commands outputs
+            if let Ok(pipeline) = store.load(&entry.uuid) {
+                for process in pipeline.get_procs()? {
+                    store.clean_outputs(&process.io)?;
+                }
+            }
+            store.remove(&entry.uuid)?;
+        }
+        store.compact()?;
+        // Invalidate cache
+        *LOGS.lock().unwrap() = None;
+        Ok(pruned)
+    }
+    /**
+    Apply the retention policy of the config file, if any.
+    */
+    pub fn prune_with_config() {
+        let logs = Config::get()
+            .ok()
+            .and_then(|e| e.options)
+            .and_then(|e| e.logs);
+        if let Some(logs) = logs {
+            if let Err(err) = Logs::prune(&logs, false) {
+                warn!("Couldn't prune logs: {}", err);
+            }
+        }
+    }
+    /**
+    Return the size in bytes of a run logs and commands outputs.
+    */
+    fn get_run_size(store: &FileStore, entry: &LogEntry) -> u64 {
+        let mut size = store.get_size(&entry.uuid).unwrap_or_default();
+        if let Ok(pipeline) = store.load(&entry.uuid) {
+            for process in pipeline.get_procs().unwrap_or_default() {
+                let (stdout_path, stderr_path) = store.get_output_paths(&process.io);
+                for path in [stdout_path, stderr_path] {
+                    size += fs::metadata(path).map(|e| e.len()).unwrap_or_default();
+                }
+            }
+        }
+        size
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{Command, Event, Pipeline, Step, StepOrParallel};
+    use miette::IntoDiagnostic;
+    use pipelight_exec::{Statuable, Status};
+    use std::path::Path;
+
+    fn get_store(name: &str) -> Result<FileStore> {
+        let directory = format!(
+            "{}/test_dir_tmp/logs_prune_{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        if Path::new(&directory).exists() {
+            fs::remove_dir_all(&directory).into_diagnostic()?;
+        }
+        Ok(FileStore::new(&directory))
+    }
+    fn log_runs(store: &FileStore, count: usize) -> Result<Vec<Pipeline>> {
+        let mut pipelines = vec![];
+        for _ in 0..count {
+            let mut p = Pipeline {
+                name: "prune".to_owned(),
+                event: Some(Event::default()),
+                ..Default::default()
+            };
+            p.set_status(Some(Status::Succeeded));
+            store.save(&p)?;
+            pipelines.push(p);
+        }
+        Ok(pipelines)
+    }
+
+    #[test]
+    fn prune_keep() -> Result<()> {
+        let store = get_store("keep")?;
+        let pipelines = log_runs(&store, 3)?;
+        let opts = LogsOpts {
+            keep: Some(1),
+            ..Default::default()
+        };
+        let pruned = Logs::prune_from(&store, &opts, true)?
+            .into_iter()
+            .map(|e| e.uuid)
+            .collect::<Vec<_>>();
+        assert_eq!(pruned, vec![pipelines[0].uuid, pipelines[1].uuid]);
+        // Nothing is deleted on a dry run
+        assert_eq!(store.entries()?.len(), 3);
+        Ok(())
+    }
+    #[test]
+    fn prune_max_age() -> Result<()> {
+        let store = get_store("max_age")?;
+        let pipelines = log_runs(&store, 1)?;
+        let opts = LogsOpts {
+            max_age: Some("PT0S".to_owned()),
+            ..Default::default()
+        };
+        let pruned = Logs::prune_from(&store, &opts, true)?;
+        assert!(pruned.iter().any(|e| e.uuid == pipelines[0].uuid));
+        Ok(())
+    }
+    #[test]
+    fn prune_delete() -> Result<()> {
+        let store = get_store("delete")?;
+        // A run with a command output
+        let mut command = Command::new("echo test");
+        command.process.io.uuid = uuid::Uuid::new_v4();
+        let (stdout_path, _) = store.get_output_paths(&command.process.io);
+        let proc_dir = Path::new(&stdout_path).parent().unwrap().to_owned();
+        fs::create_dir_all(&proc_dir).into_diagnostic()?;
+        fs::write(&stdout_path, "test\n").into_diagnostic()?;
+        let mut old = Pipeline {
+            name: "prune".to_owned(),
+            event: Some(Event::default()),
+            steps: vec![StepOrParallel::Step(Step {
+                commands: vec![command],
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        old.set_status(Some(Status::Succeeded));
+        store.save(&old)?;
+        let recent = log_runs(&store, 1)?;
+
+        let opts = LogsOpts {
+            keep: Some(1),
+            ..Default::default()
+        };
+        let pruned = Logs::prune_from(&store, &opts, false)?;
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].uuid, old.uuid);
+
+        // Snapshot and commands outputs are deleted
+        assert!(store.load(&old.uuid).is_err());
+        assert!(!proc_dir.exists());
+        // The run is dropped from the index, and the other one is kept
+        let index =
+            fs::read_to_string(format!("{}/index.jsonl", store.directory)).into_diagnostic()?;
+        assert!(!index.contains(&old.uuid.to_string()));
+        let entries = store.entries()?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].uuid, recent[0].uuid);
+        Ok(())
+    }
+}
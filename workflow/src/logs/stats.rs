@@ -0,0 +1,283 @@
+// Structs
+use crate::types::{Duration, Pipeline, Step, StepOrParallel};
+use pipelight_exec::Status;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+// Traits
+use serde::{Deserialize, Serialize};
+
+/**
+Statistics over the logged runs of a step.
+Durations are in milliseconds.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StepStats {
+    pub pipeline: String,
+    pub name: String,
+    // The number of finished runs
+    pub runs: usize,
+    pub failures: usize,
+    // The ratio of succeeded runs among finished ones, between 0 and 1
+    pub success_rate: Option<f64>,
+    pub duration_p50: Option<u64>,
+    pub duration_p95: Option<u64>,
+    // The commits on which the step has both failed and succeeded
+    pub flaky_commits: Vec<String>,
+}
+
+/**
+Statistics over the logged runs of a pipeline.
+Durations are in milliseconds.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PipelineStats {
+    pub name: String,
+    // The number of finished runs
+    pub runs: usize,
+    pub failures: usize,
+    // The ratio of succeeded runs among finished ones, between 0 and 1
+    pub success_rate: Option<f64>,
+    pub duration_p50: Option<u64>,
+    pub duration_p95: Option<u64>,
+    pub steps: Vec<StepStats>,
+}
+
+/**
+A statistics report over logged pipeline runs.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Stats {
+    pub pipelines: Vec<PipelineStats>,
+}
+
+/**
+The results of a pipeline or step over its runs.
+*/
+#[derive(Default)]
+struct Samples {
+    runs: usize,
+    failures: usize,
+    successes: usize,
+    durations: Vec<u64>,
+    // Statuses by commit
+    commits: HashMap<String, BTreeSet<bool>>,
+}
+impl Samples {
+    fn push(&mut self, status: &Option<Status>, duration: &Option<Duration>) {
+        match status {
+            Some(Status::Succeeded) => self.successes += 1,
+            Some(Status::Failed) | Some(Status::TimedOut) => self.failures += 1,
+            Some(Status::Aborted) => {}
+            // Unfinished or not run
+            _ => return,
+        }
+        self.runs += 1;
+        if let Some(mut duration) = duration.clone().filter(|e| e.ended_at.is_some()) {
+            if let Ok(duration) = duration.get() {
+                self.durations.push(duration.as_millis() as u64);
+            }
+        }
+    }
+    fn push_commit(&mut self, status: &Option<Status>, commit: &Option<String>) {
+        let succeeded = match status {
+            Some(Status::Succeeded) => true,
+            Some(Status::Failed) | Some(Status::TimedOut) => false,
+            _ => return,
+        };
+        if let Some(commit) = commit {
+            self.commits
+                .entry(commit.to_owned())
+                .or_default()
+                .insert(succeeded);
+        }
+    }
+    fn get_success_rate(&self) -> Option<f64> {
+        match self.runs {
+            0 => None,
+            runs => Some(self.successes as f64 / runs as f64),
+        }
+    }
+    /**
+    Return the nearest-rank percentile of the durations.
+    */
+    fn get_percentile(&self, percentile: f64) -> Option<u64> {
+        let mut durations = self.durations.clone();
+        durations.sort();
+        let rank = (percentile * durations.len() as f64).ceil() as usize;
+        durations.get(rank.max(1) - 1).copied()
+    }
+    fn get_flaky_commits(&self) -> Vec<String> {
+        let mut commits: Vec<String> = self
+            .commits
+            .iter()
+            .filter(|(_, statuses)| statuses.len() > 1)
+            .map(|(commit, _)| commit.to_owned())
+            .collect();
+        commits.sort();
+        commits
+    }
+}
+
+impl From<&Vec<Pipeline>> for Stats {
+    /**
+    Compute statistics from pipeline runs, grouped by pipeline name
+    in order of first appearance.
+    Steps reused from a parent run are ignored.
+    */
+    fn from(pipelines: &Vec<Pipeline>) -> Self {
+        let mut names: Vec<String> = vec![];
+        let mut runs: HashMap<String, Samples> = HashMap::new();
+        let mut step_names: HashMap<String, Vec<String>> = HashMap::new();
+        let mut step_runs: HashMap<(String, String), Samples> = HashMap::new();
+
+        for pipeline in pipelines {
+            if !runs.contains_key(&pipeline.name) {
+                names.push(pipeline.name.clone());
+            }
+            runs.entry(pipeline.name.clone())
+                .or_default()
+                .push(&pipeline.status, &pipeline.duration);
+
+            let commit = pipeline
+                .event
+                .as_ref()
+                .and_then(|e| e.trigger.get_commit().ok().flatten());
+            let steps = pipeline.steps.iter().flat_map(|e| match e {
+                StepOrParallel::Step(res) => vec![res],
+                StepOrParallel::Parallel(res) => res.steps.iter().collect::<Vec<&Step>>(),
+            });
+            for step in steps.filter(|e| e.reused != Some(true)) {
+                let key = (pipeline.name.clone(), step.name.clone());
+                if !step_runs.contains_key(&key) {
+                    step_names
+                        .entry(pipeline.name.clone())
+                        .or_default()
+                        .push(step.name.clone());
+                }
+                let samples = step_runs.entry(key).or_default();
+                samples.push(&step.status, &step.duration);
+                samples.push_commit(&step.status, &commit);
+            }
+        }
+
+        let pipelines = names
+            .iter()
+            .map(|name| {
+                let samples = &runs[name];
+                let steps = step_names
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|step| {
+                        let samples = &step_runs[&(name.clone(), step.clone())];
+                        StepStats {
+                            pipeline: name.clone(),
+                            name: step.clone(),
+                            runs: samples.runs,
+                            failures: samples.failures,
+                            success_rate: samples.get_success_rate(),
+                            duration_p50: samples.get_percentile(0.5),
+                            duration_p95: samples.get_percentile(0.95),
+                            flaky_commits: samples.get_flaky_commits(),
+                        }
+                    })
+                    .collect();
+                PipelineStats {
+                    name: name.clone(),
+                    runs: samples.runs,
+                    failures: samples.failures,
+                    success_rate: samples.get_success_rate(),
+                    duration_p50: samples.get_percentile(0.5),
+                    duration_p95: samples.get_percentile(0.95),
+                    steps,
+                }
+            })
+            .collect();
+        Stats { pipelines }
+    }
+}
+
+impl Stats {
+    /**
+    Return every step statistics, the most frequently failing steps first.
+    */
+    pub fn get_failing_steps(&self) -> Vec<StepStats> {
+        let mut steps: Vec<StepStats> = self
+            .pipelines
+            .iter()
+            .flat_map(|e| e.steps.clone())
+            .collect();
+        steps.sort_by_key(|e| Reverse(e.failures));
+        steps
+    }
+    /**
+    Return the steps that have both failed and succeeded on a same commit.
+    */
+    pub fn get_flaky_steps(&self) -> Vec<StepStats> {
+        self.pipelines
+            .iter()
+            .flat_map(|e| e.steps.clone())
+            .filter(|e| !e.flaky_commits.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{Event, Trigger, TriggerBranch};
+
+    fn make_run(commit: &str, statuses: [Status; 2]) -> Pipeline {
+        let steps = ["build", "test"]
+            .iter()
+            .zip(statuses)
+            .map(|(name, status)| {
+                StepOrParallel::Step(Step {
+                    name: name.to_string(),
+                    status: Some(status),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        Pipeline {
+            name: "stats".to_owned(),
+            status: Some(Status::Succeeded),
+            event: Some(Event {
+                trigger: Trigger::TriggerBranch(TriggerBranch {
+                    commit: Some(commit.to_owned()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            steps,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flaky_steps() {
+        let pipelines = vec![
+            make_run("a1", [Status::Succeeded, Status::Failed]),
+            make_run("a1", [Status::Succeeded, Status::Succeeded]),
+            make_run("b2", [Status::Succeeded, Status::Failed]),
+        ];
+        let stats = Stats::from(&pipelines);
+        let steps = stats.get_failing_steps();
+        assert_eq!(steps[0].name, "test");
+        assert_eq!(steps[0].failures, 2);
+        assert_eq!(steps[0].flaky_commits, vec!["a1".to_owned()]);
+        assert_eq!(stats.get_flaky_steps().len(), 1);
+        assert_eq!(stats.pipelines[0].success_rate, Some(1.0));
+    }
+    #[test]
+    fn percentiles() {
+        let samples = Samples {
+            durations: (1..=20).collect(),
+            ..Default::default()
+        };
+        assert_eq!(samples.get_percentile(0.5), Some(10));
+        assert_eq!(samples.get_percentile(0.95), Some(19));
+        assert_eq!(Samples::default().get_percentile(0.5), None);
+    }
+}
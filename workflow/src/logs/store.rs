@@ -0,0 +1,528 @@
+// Structs
+use crate::pipeline::Filters;
+use crate::types::{Duration, Event, Pipeline};
+use pipelight_exec::{Io, Status};
+use std::collections::HashMap;
+use uuid::Uuid;
+// Dates
+use chrono::{DateTime, Local};
+// Unix process manipulation
+use rustix::fs::{flock, FlockOperation};
+use rustix::process::{test_kill_process, Pid};
+// Traits
+use serde::{Deserialize, Serialize};
+// Filesystem manipulation
+use pipelight_utils::file::read_last_line;
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+// Error Handling
+use log::{trace, warn};
+use miette::{Error, IntoDiagnostic, Result};
+// Global vars
+use crate::globals::INDEXED;
+use crate::pipeline::OUTDIR;
+
+/**
+The name of the run metadata index inside the logs directory.
+*/
+pub const INDEX_FILE: &str = "index.jsonl";
+/**
+The name of the file locked while the logs index is rewritten.
+*/
+pub const LOCK_FILE: &str = "index.lock";
+
+/**
+The metadata of a pipeline run, as recorded in the logs index.
+It is enough to list and filter runs without reading their full logs.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct LogEntry {
+    pub uuid: Uuid,
+    pub name: String,
+    pub event: Option<Event>,
+    pub status: Option<Status>,
+    pub duration: Option<Duration>,
+    pub parent: Option<Uuid>,
+    // Set when the run logs have been deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed: Option<bool>,
+}
+impl From<&Pipeline> for LogEntry {
+    fn from(e: &Pipeline) -> Self {
+        // Only keep what is actually stored
+        let duration = e.duration.clone().map(|mut duration| {
+            duration.computed = None;
+            duration
+        });
+        LogEntry {
+            uuid: e.uuid,
+            name: e.name.clone(),
+            event: e.event.clone(),
+            status: e.status.clone(),
+            duration,
+            parent: e.parent,
+            removed: None,
+        }
+    }
+}
+
+impl LogEntry {
+    /**
+    Return the date the run has been triggered at.
+    */
+    pub fn get_date(&self) -> Option<DateTime<Local>> {
+        self.event
+            .as_ref()
+            .and_then(|e| e.date.parse::<DateTime<Local>>().ok())
+    }
+    /**
+    Return the run status.
+    A run logged as running whose process is gone has been aborted.
+    */
+    pub fn get_status(&self) -> Option<Status> {
+        if self.status == Some(Status::Running) && !self.is_running() {
+            Some(Status::Aborted)
+        } else {
+            self.status.clone()
+        }
+    }
+    /**
+    Check if the run is still being executed,
+    by testing the triggering pipelight instance pid.
+    */
+    pub fn is_running(&self) -> bool {
+        if self.status != Some(Status::Started) && self.status != Some(Status::Running) {
+            return false;
+        }
+        match self
+            .event
+            .as_ref()
+            .and_then(|e| e.pid)
+            .and_then(Pid::from_raw)
+        {
+            Some(pid) => test_kill_process(pid).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/**
+A storage backend for pipeline runs logs.
+*/
+pub trait LogStore {
+    /**
+    Save the current state of a run.
+    */
+    fn save(&self, pipeline: &Pipeline) -> Result<()>;
+    /**
+    Return the last saved state of a run.
+    */
+    fn load(&self, uuid: &Uuid) -> Result<Pipeline>;
+    /**
+    Return the metadata of every stored run, in recording order,
+    without reading their full logs.
+    */
+    fn entries(&self) -> Result<Vec<LogEntry>>;
+    /**
+    Delete a run.
+    */
+    fn remove(&self, uuid: &Uuid) -> Result<()>;
+    /**
+    Return the size in bytes a run takes in the store.
+    */
+    fn get_size(&self, uuid: &Uuid) -> Result<u64>;
+    /**
+    Return the last saved state of every stored run.
+    */
+    fn load_all(&self) -> Result<Vec<Pipeline>> {
+        let mut pipelines = vec![];
+        for entry in self.entries()? {
+            match self.load(&entry.uuid) {
+                Ok(pipeline) => pipelines.push(pipeline),
+                Err(_err) => warn!("Stripped corrupted log of run: {}", entry.uuid),
+            }
+        }
+        Ok(pipelines)
+    }
+}
+
+/**
+The default logs storage, a directory of JSON files:
+- one snapshot file per run, `<uuid>.json`,
+  that holds the run last state on a single line and is atomically replaced on save,
+- an append-only index, `index.jsonl`, of run metadata records.
+  A record is only appended when the metadata of a run changes,
+  the last record of a run wins.
+
+Logs written by older versions (a full snapshot appended on every save)
+are migrated on first use.
+*/
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FileStore {
+    pub directory: String,
+    // The directory of the runs commands outputs,
+    // the default processes outputs directory if none.
+    pub proc_directory: Option<String>,
+}
+impl Default for FileStore {
+    fn default() -> Self {
+        FileStore {
+            directory: OUTDIR.lock().unwrap().clone(),
+            proc_directory: None,
+        }
+    }
+}
+impl FileStore {
+    /**
+    A store in the provided directory,
+    that keeps the runs commands outputs in its `proc` subdirectory.
+    */
+    pub fn new(directory: &str) -> Self {
+        FileStore {
+            directory: directory.to_owned(),
+            proc_directory: Some(format!("{}/proc", directory)),
+        }
+    }
+    /**
+    Return the paths of the files a run command stdout and stderr are written to.
+    */
+    pub fn get_output_paths(&self, io: &Io) -> (String, String) {
+        match &self.proc_directory {
+            Some(directory) => io.get_paths_in(directory),
+            None => io.get_paths(),
+        }
+    }
+    /**
+    Delete the files a run command outputs are written to.
+    */
+    pub fn clean_outputs(&self, io: &Io) -> Result<()> {
+        match &self.proc_directory {
+            Some(directory) => io.clean_in(directory),
+            None => io.clean(),
+        }
+        .into_diagnostic()
+    }
+    fn get_snapshot_path(&self, uuid: &Uuid) -> String {
+        format!("{}/{}.json", self.directory, uuid)
+    }
+    fn get_index_path(&self) -> String {
+        format!("{}/{}", self.directory, INDEX_FILE)
+    }
+    /**
+    Lock the logs index until the returned file is dropped.
+    Rewriting the index takes an exclusive lock,
+    so that records appended by concurrent processes (shared lock) are not lost.
+    */
+    fn lock(&self, operation: FlockOperation) -> Result<File> {
+        let f = File::options()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(format!("{}/{}", self.directory, LOCK_FILE))
+            .into_diagnostic()?;
+        flock(&f, operation).into_diagnostic()?;
+        Ok(f)
+    }
+    /**
+    Atomically replace a file content:
+    write to a temporary file and rename it over the destination.
+    */
+    fn write_atomic(path: &str, content: &str) -> Result<()> {
+        let tmp = format!("{}.{}.tmp", path, Uuid::new_v4());
+        let mut f = File::create(&tmp).into_diagnostic()?;
+        f.write_all(content.as_bytes()).into_diagnostic()?;
+        f.sync_all().into_diagnostic()?;
+        fs::rename(&tmp, path).into_diagnostic()?;
+        Ok(())
+    }
+    /**
+    Append a metadata record to the index.
+    */
+    fn append(&self, entry: &LogEntry) -> Result<()> {
+        let json = serde_json::to_string(entry).into_diagnostic()? + "\n";
+        let _lock = self.lock(FlockOperation::LockShared)?;
+        let mut f = File::options()
+            .append(true)
+            .create(true)
+            .open(self.get_index_path())
+            .into_diagnostic()?;
+        f.write_all(json.as_bytes()).into_diagnostic()?;
+        Ok(())
+    }
+    /**
+    Ensure the logs directory exists and is migrated.
+    */
+    fn ensure(&self) -> Result<()> {
+        fs::create_dir_all(&self.directory).into_diagnostic()?;
+        if !Path::new(&self.get_index_path()).exists() {
+            let _lock = self.lock(FlockOperation::LockExclusive)?;
+            // Another process may have migrated the directory in the meantime.
+            if !Path::new(&self.get_index_path()).exists() {
+                self.migrate()?;
+            }
+        }
+        Ok(())
+    }
+    /**
+    Build the index from the existing log files,
+    and rewrite the legacy ones (a full snapshot per line) to their last state.
+    The caller must hold the exclusive index lock.
+    */
+    fn migrate(&self) -> Result<()> {
+        let message = format!("Migrating log directory: {}", self.directory);
+        trace!("{}", message);
+        let mut pipelines = vec![];
+        for entry in fs::read_dir(&self.directory).into_diagnostic()? {
+            let path = entry.into_diagnostic()?.path();
+            if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let pipeline = read_last_line(&path)
+                .and_then(|json| serde_json::from_str::<Pipeline>(&json).into_diagnostic());
+            match pipeline {
+                Ok(pipeline) => {
+                    let json = serde_json::to_string(&pipeline).into_diagnostic()? + "\n";
+                    FileStore::write_atomic(&path.display().to_string(), &json)?;
+                    pipelines.push(pipeline);
+                }
+                Err(_err) => warn!("Stripped corrupted log file: {}", path.display()),
+            }
+        }
+        pipelines = Filters::sort_by_date_asc(pipelines)?;
+        let index = pipelines
+            .iter()
+            .map(|e| serde_json::to_string(&LogEntry::from(e)).unwrap() + "\n")
+            .collect::<String>();
+        FileStore::write_atomic(&self.get_index_path(), &index)?;
+        Ok(())
+    }
+    /**
+    Rewrite the index with a single record per stored run.
+    */
+    pub fn compact(&self) -> Result<()> {
+        self.ensure()?;
+        let _lock = self.lock(FlockOperation::LockExclusive)?;
+        let index = self
+            .entries()?
+            .iter()
+            .map(|e| serde_json::to_string(e).unwrap() + "\n")
+            .collect::<String>();
+        FileStore::write_atomic(&self.get_index_path(), &index)?;
+        Ok(())
+    }
+}
+
+impl LogStore for FileStore {
+    fn save(&self, pipeline: &Pipeline) -> Result<()> {
+        self.ensure()?;
+        let json = serde_json::to_string(pipeline).into_diagnostic()? + "\n";
+        FileStore::write_atomic(&self.get_snapshot_path(&pipeline.uuid), &json)?;
+
+        // Only record metadata changes
+        let entry = LogEntry::from(pipeline);
+        let key = (self.directory.clone(), pipeline.uuid);
+        let mut indexed = INDEXED.lock().unwrap();
+        if indexed.get(&key) != Some(&entry) {
+            self.append(&entry)?;
+            indexed.insert(key, entry);
+        }
+        Ok(())
+    }
+    fn load(&self, uuid: &Uuid) -> Result<Pipeline> {
+        let json = read_last_line(Path::new(&self.get_snapshot_path(uuid)))?;
+        serde_json::from_str::<Pipeline>(&json).into_diagnostic()
+    }
+    fn get_size(&self, uuid: &Uuid) -> Result<u64> {
+        let metadata = fs::metadata(self.get_snapshot_path(uuid)).into_diagnostic()?;
+        Ok(metadata.len())
+    }
+    fn entries(&self) -> Result<Vec<LogEntry>> {
+        // Directory Safe-guard
+        if !Path::new(&self.directory).exists() {
+            let message = "No logs to display.";
+            return Err(Error::msg(message));
+        }
+        self.ensure()?;
+        let f = File::open(self.get_index_path()).into_diagnostic()?;
+
+        // Records in first seen order, the last record of a run wins
+        let mut entries: Vec<Option<LogEntry>> = vec![];
+        let mut positions: HashMap<Uuid, usize> = HashMap::new();
+        for line in BufReader::new(f).lines() {
+            let line = line.into_diagnostic()?;
+            let entry = match serde_json::from_str::<LogEntry>(&line) {
+                Ok(entry) => entry,
+                Err(_err) => {
+                    warn!("Stripped corrupted log index record: {}", line);
+                    continue;
+                }
+            };
+            let removed = entry.removed == Some(true);
+            match positions.get(&entry.uuid) {
+                Some(i) if removed => {
+                    entries[*i] = None;
+                    positions.remove(&entry.uuid);
+                }
+                Some(i) => entries[*i] = Some(entry),
+                None if removed => {}
+                None => {
+                    positions.insert(entry.uuid, entries.len());
+                    entries.push(Some(entry));
+                }
+            }
+        }
+        Ok(entries.into_iter().flatten().collect())
+    }
+    fn remove(&self, uuid: &Uuid) -> Result<()> {
+        let path = self.get_snapshot_path(uuid);
+        let path = Path::new(&path);
+        if path.exists() && path.is_file() {
+            fs::remove_file(path).into_diagnostic()?;
+        }
+        let mut indexed = INDEXED.lock().unwrap();
+        let entry = indexed
+            .remove(&(self.directory.clone(), *uuid))
+            .or_else(|| {
+                self.entries()
+                    .ok()
+                    .and_then(|e| e.into_iter().find(|e| e.uuid == *uuid))
+            });
+        if let Some(entry) = entry {
+            self.append(&LogEntry {
+                removed: Some(true),
+                ..entry
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pipelight_exec::Statuable;
+
+    fn get_store(name: &str) -> Result<FileStore> {
+        let directory = format!("{}/test_dir_tmp/logs_{}", env!("CARGO_MANIFEST_DIR"), name);
+        if Path::new(&directory).exists() {
+            fs::remove_dir_all(&directory).into_diagnostic()?;
+        }
+        Ok(FileStore::new(&directory))
+    }
+    fn count_records(store: &FileStore) -> Result<usize> {
+        let index = fs::read_to_string(store.get_index_path()).into_diagnostic()?;
+        Ok(index.lines().count())
+    }
+
+    #[test]
+    fn save_and_index() -> Result<()> {
+        let store = get_store("save")?;
+        let mut p = Pipeline {
+            event: Some(Event::default()),
+            ..Default::default()
+        };
+        p.set_status(Some(Status::Running));
+        store.save(&p)?;
+        // Unchanged metadata are not recorded
+        store.save(&p)?;
+        assert_eq!(count_records(&store)?, 1);
+
+        p.set_status(Some(Status::Succeeded));
+        store.save(&p)?;
+        assert_eq!(count_records(&store)?, 2);
+
+        // Single line snapshot
+        let snapshot = fs::read_to_string(store.get_snapshot_path(&p.uuid)).into_diagnostic()?;
+        assert_eq!(snapshot.lines().count(), 1);
+        assert_eq!(store.load(&p.uuid)?.status, Some(Status::Succeeded));
+
+        let entries = store.entries()?;
+        assert_eq!(entries, vec![LogEntry::from(&p)]);
+
+        store.compact()?;
+        assert_eq!(count_records(&store)?, 1);
+        Ok(())
+    }
+    #[test]
+    fn index_per_directory() -> Result<()> {
+        let store = get_store("index_a")?;
+        let other = get_store("index_b")?;
+        let p = Pipeline {
+            event: Some(Event::default()),
+            ..Default::default()
+        };
+        // A run saved in another directory is indexed there too.
+        store.save(&p)?;
+        other.save(&p)?;
+        assert_eq!(other.entries()?, vec![LogEntry::from(&p)]);
+        Ok(())
+    }
+    #[test]
+    fn remove() -> Result<()> {
+        let store = get_store("remove")?;
+        let p = Pipeline {
+            event: Some(Event::default()),
+            ..Default::default()
+        };
+        store.save(&p)?;
+        store.remove(&p.uuid)?;
+        assert!(store.entries()?.is_empty());
+        assert!(store.load(&p.uuid).is_err());
+        Ok(())
+    }
+    #[test]
+    fn migrate_legacy_logs() -> Result<()> {
+        let store = get_store("migrate")?;
+        fs::create_dir_all(&store.directory).into_diagnostic()?;
+        // A legacy log file, with a snapshot appended on every status change
+        let mut p = Pipeline {
+            event: Some(Event::default()),
+            ..Default::default()
+        };
+        let mut legacy = "".to_owned();
+        for status in [Status::Started, Status::Running, Status::Failed] {
+            p.set_status(Some(status));
+            legacy += &(serde_json::to_string(&p).into_diagnostic()? + "\n");
+        }
+        fs::write(store.get_snapshot_path(&p.uuid), legacy).into_diagnostic()?;
+
+        let entries = store.entries()?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, Some(Status::Failed));
+        let snapshot = fs::read_to_string(store.get_snapshot_path(&p.uuid)).into_diagnostic()?;
+        assert_eq!(snapshot.lines().count(), 1);
+        Ok(())
+    }
+    #[test]
+    fn concurrent_migration() -> Result<()> {
+        let store = get_store("concurrent")?;
+        fs::create_dir_all(&store.directory).into_diagnostic()?;
+        let p = Pipeline {
+            event: Some(Event::default()),
+            ..Default::default()
+        };
+        let legacy = serde_json::to_string(&p).into_diagnostic()? + "\n";
+        fs::write(store.get_snapshot_path(&p.uuid), legacy).into_diagnostic()?;
+
+        // Every process saving a run may find the directory unmigrated.
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    let p = Pipeline {
+                        event: Some(Event::default()),
+                        ..Default::default()
+                    };
+                    store.save(&p)
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap()?;
+        }
+        assert_eq!(store.entries()?.len(), 9);
+        Ok(())
+    }
+}
@@ -1,17 +1,70 @@
 // Structs
-use crate::types::Pipeline;
+use crate::logs::store::LogEntry;
+use crate::types::{Pipeline, Trigger};
 use pipelight_exec::Status;
+use pipelight_utils::git::Flag;
 use uuid::Uuid;
 // Date and Time
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use pipelight_exec::dates::convert::iso8601_to_std_duration;
 // Collections
 use std::collections::HashMap;
 // Error Handling
 use log::warn;
 use miette::{Error, Result};
 
+/**
+Criteria to select pipeline runs from logs.
+Runs must match every provided criterion.
+*/
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct Query {
+    pub name: Option<String>,
+    pub status: Option<Status>,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub action: Option<Flag>,
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+    // A commit hash or the beginning of it
+    pub commit: Option<String>,
+    // The number of most recent runs to keep
+    pub limit: Option<usize>,
+}
+
 pub struct Filters;
 impl Filters {
+    /**
+    Return the runs from the logs index that match the query, sorted by ascending date.
+    Only the metadata are read, the matching runs full logs can then be loaded.
+    */
+    pub fn query(entries: Vec<LogEntry>, query: &Query) -> Result<Vec<LogEntry>> {
+        let mut entries = entries;
+        entries.sort_by_key(|e| e.get_date());
+        if let Some(name) = &query.name {
+            entries.retain(|e| &e.name == name);
+        }
+        if query.status.is_some() {
+            entries.retain(|e| e.get_status() == query.status);
+        }
+        if let Some(branch) = &query.branch {
+            entries = Filters::filter_by_branch(entries, branch)?;
+        }
+        if let Some(tag) = &query.tag {
+            entries = Filters::filter_by_tag(entries, tag)?;
+        }
+        if let Some(action) = &query.action {
+            entries = Filters::filter_by_action(entries, action)?;
+        }
+        if let Some(commit) = &query.commit {
+            entries = Filters::filter_by_commit(entries, commit)?;
+        }
+        entries = Filters::filter_by_date(entries, query.since, query.until)?;
+        if let Some(limit) = query.limit {
+            entries = Filters::limit(entries, limit)?;
+        }
+        Ok(entries)
+    }
     /**
     Keep only one pipeline among those which have the same name.
     */
@@ -115,4 +168,190 @@ impl Filters {
         pipelines.retain(|e| e.name == name);
         Ok(pipelines)
     }
+    /**
+    Return the runs triggered on the provided git branch.
+    */
+    pub fn filter_by_branch(entries: Vec<LogEntry>, branch: &str) -> Result<Vec<LogEntry>> {
+        let mut entries = entries;
+        entries.retain(|e| match e.event.as_ref().map(|e| &e.trigger) {
+            Some(Trigger::TriggerBranch(trigger)) => trigger.branch.as_deref() == Some(branch),
+            _ => false,
+        });
+        Ok(entries)
+    }
+    /**
+    Return the runs triggered on the provided git tag.
+    */
+    pub fn filter_by_tag(entries: Vec<LogEntry>, tag: &str) -> Result<Vec<LogEntry>> {
+        let mut entries = entries;
+        entries.retain(|e| match e.event.as_ref().map(|e| &e.trigger) {
+            Some(Trigger::TriggerTag(trigger)) => trigger.tag.as_deref() == Some(tag),
+            _ => false,
+        });
+        Ok(entries)
+    }
+    /**
+    Return the runs triggered by the provided action.
+    */
+    pub fn filter_by_action(entries: Vec<LogEntry>, action: &Flag) -> Result<Vec<LogEntry>> {
+        let mut entries = entries;
+        entries.retain(|e| match &e.event {
+            Some(event) => event.trigger.get_action().ok().flatten().as_ref() == Some(action),
+            None => false,
+        });
+        Ok(entries)
+    }
+    /**
+    Return the runs triggered on a commit starting with the provided hash.
+    */
+    pub fn filter_by_commit(entries: Vec<LogEntry>, commit: &str) -> Result<Vec<LogEntry>> {
+        let mut entries = entries;
+        entries.retain(|e| match &e.event {
+            Some(event) => event
+                .trigger
+                .get_commit()
+                .ok()
+                .flatten()
+                .is_some_and(|e| e.starts_with(commit)),
+            None => false,
+        });
+        Ok(entries)
+    }
+    /**
+    Return the runs triggered between the provided dates (inclusive).
+    */
+    pub fn filter_by_date(
+        entries: Vec<LogEntry>,
+        since: Option<DateTime<Local>>,
+        until: Option<DateTime<Local>>,
+    ) -> Result<Vec<LogEntry>> {
+        let mut entries = entries;
+        if since.is_none() && until.is_none() {
+            return Ok(entries);
+        }
+        entries.retain(|e| match e.get_date() {
+            Some(date) => {
+                since.is_none_or(|since| since <= date) && until.is_none_or(|until| date <= until)
+            }
+            None => false,
+        });
+        Ok(entries)
+    }
+    /**
+    Keep only the provided number of last runs.
+    */
+    pub fn limit(entries: Vec<LogEntry>, limit: usize) -> Result<Vec<LogEntry>> {
+        let mut entries = entries;
+        let start = entries.len().saturating_sub(limit);
+        entries.drain(..start);
+        Ok(entries)
+    }
+    /**
+    Parse a date to filter pipelines with. Either:
+    - a date or a date and time (ex: "2024-05-01", "2024-05-01 18:30:00"),
+    - an RFC3339 date (ex: "2024-05-01T18:30:00+02:00"),
+    - an ISO8601 duration counted back from now (ex: "P7D" for a week ago).
+    */
+    pub fn parse_date(date: &str) -> Result<DateTime<Local>> {
+        if let Ok(date) = date.parse::<DateTime<Local>>() {
+            return Ok(date);
+        }
+        if let Ok(date) = NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S") {
+            if let Some(date) = Local.from_local_datetime(&date).earliest() {
+                return Ok(date);
+            }
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            let date = date.and_hms_opt(0, 0, 0).unwrap();
+            if let Some(date) = Local.from_local_datetime(&date).earliest() {
+                return Ok(date);
+            }
+        }
+        if let Ok(duration) = iso8601_to_std_duration(date) {
+            if let Ok(duration) = chrono::Duration::from_std(duration) {
+                return Ok(Local::now() - duration);
+            }
+        }
+        let message = format!(
+            "The date {} is not valid (ex: \"2024-05-01\", \"2024-05-01 18:30:00\", \"P7D\")",
+            date
+        );
+        Err(Error::msg(message))
+    }
+    /**
+    Parse a date to filter pipelines with, like `parse_date`,
+    but a date without time is the end of that day,
+    so that it includes the runs of that day.
+    */
+    pub fn parse_end_date(date: &str) -> Result<DateTime<Local>> {
+        if let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            let date = date.and_hms_nano_opt(23, 59, 59, 999_999_999).unwrap();
+            if let Some(date) = Local.from_local_datetime(&date).latest() {
+                return Ok(date);
+            }
+        }
+        Filters::parse_date(date)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{Event, TriggerBranch};
+
+    fn make_run(status: Status, branch: &str, date: &str) -> LogEntry {
+        let pipeline = Pipeline {
+            status: Some(status),
+            event: Some(Event {
+                trigger: Trigger::TriggerBranch(TriggerBranch {
+                    branch: Some(branch.to_owned()),
+                    commit: Some("8f3a9c1d".to_owned()),
+                    ..Default::default()
+                }),
+                date: Filters::parse_date(date).unwrap().to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        LogEntry::from(&pipeline)
+    }
+
+    #[test]
+    fn query() -> Result<()> {
+        let pipelines = vec![
+            make_run(Status::Failed, "main", "2024-05-01"),
+            make_run(Status::Failed, "main", "2024-05-03"),
+            make_run(Status::Succeeded, "main", "2024-05-04"),
+            make_run(Status::Failed, "dev", "2024-05-05"),
+        ];
+        let query = Query {
+            status: Some(Status::Failed),
+            branch: Some("main".to_owned()),
+            since: Some(Filters::parse_date("2024-05-02")?),
+            commit: Some("8f3a".to_owned()),
+            ..Default::default()
+        };
+        let res = Filters::query(pipelines.clone(), &query)?;
+        assert_eq!(res, vec![pipelines[1].clone()]);
+
+        let query = Query {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let res = Filters::query(pipelines.clone(), &query)?;
+        assert_eq!(res, pipelines[2..].to_vec());
+        Ok(())
+    }
+    #[test]
+    fn parse_date() -> Result<()> {
+        assert!(Filters::parse_date("2024-05-01 18:30:00").is_ok());
+        assert!(Filters::parse_date("2024-05-01T18:30:00+02:00").is_ok());
+        assert!(Filters::parse_date("P7D")? < Local::now());
+        assert!(Filters::parse_date("last week").is_err());
+        // A single day includes its runs
+        let day = Filters::parse_date("2024-05-01")?;
+        let run = Filters::parse_date("2024-05-01 18:30:00")?;
+        assert!(day < run && run < Filters::parse_end_date("2024-05-01")?);
+        Ok(())
+    }
 }
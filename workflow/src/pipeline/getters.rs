@@ -2,11 +2,18 @@ use crate::error::IsError;
 use crate::traits::Getters;
 use crate::types::{Config, Pipeline};
 use log::LevelFilter;
+use pipelight_exec::dates::convert::iso8601_to_std_duration;
 use pipelight_exec::Process;
+use std::time;
 
 // Error Handling
 use miette::{Error, Result};
 
+/**
+The default maximum size of a command stdout and stderr kept in the logs (1 MiB).
+*/
+const DEFAULT_MAX_OUTPUT: usize = 1 << 20;
+
 impl Getters<Pipeline> for Pipeline {
     /**
     Return pipelines from config file.
@@ -76,4 +83,56 @@ impl Pipeline {
             Ok(LevelFilter::Error)
         }
     }
+    /**
+    Return the pipeline maximum execution time if any.
+    */
+    pub fn get_timeout(&self) -> Option<time::Duration> {
+        if let Some(options) = &self.options {
+            if let Some(timeout) = &options.timeout {
+                return iso8601_to_std_duration(timeout).ok();
+            }
+        }
+        None
+    }
+    /**
+    Return the maximum size of a command stdout and stderr kept in the logs.
+    The full outputs are left in the process output files.
+    */
+    pub fn get_max_output(&self) -> usize {
+        self.options
+            .as_ref()
+            .and_then(|e| e.max_output)
+            .map_or(DEFAULT_MAX_OUTPUT, |e| e as usize)
+    }
+    /**
+    Return the time left before the pipeline timeout is reached, if any.
+    */
+    pub fn get_time_left(&self) -> Result<Option<time::Duration>> {
+        if let Some(timeout) = self.get_timeout() {
+            if let Some(mut duration) = self.duration.clone() {
+                let elapsed = duration.get()?;
+                Ok(Some(timeout.saturating_sub(elapsed)))
+            } else {
+                Ok(Some(timeout))
+            }
+        } else {
+            Ok(None)
+        }
+    }
+    /**
+    Return an error for an unknown step name, with the available steps as hint.
+    */
+    pub(super) fn unknown_step(&self, name: &str) -> Error {
+        let message = format!("Couldn't find step: {}", name);
+
+        let mut hint = "".to_owned();
+        hint += "Available steps are:\n\n";
+        for name in self.steps.iter().flat_map(|e| e.get_names()) {
+            hint += &format!("{}\n", name);
+        }
+        match IsError::new(&message, &hint) {
+            Ok(diag) => diag.into(),
+            Err(e) => e,
+        }
+    }
 }
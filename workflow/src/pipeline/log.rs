@@ -1,17 +1,13 @@
 // Structs
+use crate::logs::store::FileStore;
 use crate::types::{Pipeline, StepOrParallel};
 // Traits
+use crate::logs::store::LogStore;
 use pipelight_exec::{Statuable, Status};
 // Globals
 use once_cell::sync::Lazy;
-use std::io::Write;
 use std::sync::{Arc, Mutex};
-// Fylesystem manipulation
-use std::fs;
-use std::fs::{create_dir_all, File};
-use std::path::Path;
 // Error Handling
-use log::error;
 use miette::{IntoDiagnostic, Result};
 
 /**
@@ -22,19 +18,10 @@ pub static OUTDIR: Lazy<Arc<Mutex<String>>> =
 
 impl Pipeline {
     /**
-    Delete the pipeline log file.
+    Delete the pipeline logs.
     */
     pub fn clean(&self) -> Result<()> {
-        //Ensure dir
-        let dir = OUTDIR.lock().unwrap();
-        fs::create_dir_all(dir.clone()).into_diagnostic()?;
-
-        let stdout_path = format!("{}/{}.json", dir.clone(), self.uuid);
-
-        let path = Path::new(&stdout_path);
-        if path.exists() && path.is_file() {
-            fs::remove_file(path).into_diagnostic()?;
-        }
+        FileStore::default().remove(&self.uuid)?;
         // // Subprocess tmp files
         let processes = self.get_procs()?;
         for process in processes {
@@ -43,24 +30,17 @@ impl Pipeline {
         Ok(())
     }
     /**
-    Print the pipeline status as JSON inside a log file.
+    Save the pipeline current state into the logs.
     */
     pub fn log(&self) -> Result<()> {
-        //Ensure dir
-        let dir = OUTDIR.lock().unwrap();
-        fs::create_dir_all(dir.clone()).into_diagnostic()?;
-
-        let json = serde_json::to_string(&self).unwrap() + "\n";
-
-        let stdout_path = format!("{}/{}.json", dir.clone(), self.uuid);
-        let mut f = File::options()
-            .append(true)
-            .write(true)
-            .create(true)
-            .open(stdout_path)
-            .into_diagnostic()?;
-        f.write_all(json.as_bytes()).into_diagnostic()?;
-        Ok(())
+        FileStore::default().save(self)
+    }
+    /**
+    Read the pipeline last state from its log file.
+    Bypasses the logs cache, to keep up with a running pipeline.
+    */
+    pub fn read_log(&self) -> Result<Pipeline> {
+        FileStore::default().load(&self.uuid)
     }
     /**
      * On demand,
@@ -68,12 +48,17 @@ impl Pipeline {
      * Beware: Concurent std read/write
      */
     pub fn hydrate(&mut self) -> Result<()> {
+        let max_output = Some(self.get_max_output());
         for step_or_parallel in &mut self.steps {
             match step_or_parallel {
                 StepOrParallel::Step(step) => {
                     for command in &mut step.commands {
                         if command.get_status() == Some(Status::Running) {
-                            command.process.io.read().into_diagnostic()?;
+                            command
+                                .process
+                                .io
+                                .read_capped(max_output)
+                                .into_diagnostic()?;
                         }
                     }
                 }
@@ -81,7 +66,11 @@ impl Pipeline {
                     for step in &mut parallel.steps {
                         for command in &mut step.commands {
                             if command.get_status() == Some(Status::Running) {
-                                command.process.io.read().into_diagnostic()?;
+                                command
+                                    .process
+                                    .io
+                                    .read_capped(max_output)
+                                    .into_diagnostic()?;
                             }
                         }
                     }
@@ -2,8 +2,11 @@ mod filters;
 pub mod getters;
 mod is;
 mod log;
+mod resume;
 mod run;
+mod select;
 mod stop;
 
 // Re-export
-pub use filters::Filters;
+pub use filters::{Filters, Query};
+pub use log::OUTDIR;
@@ -0,0 +1,142 @@
+// Structs
+use crate::types::{Command, Fallback, Parallel, Pipeline, Step, StepOrParallel};
+use uuid::Uuid;
+// Traits
+use pipelight_exec::{Statuable, Status};
+// Error Handling
+use miette::{Error, Result};
+
+impl Pipeline {
+    /**
+    Return a new run of a logged pipeline, resumed from the given step
+    or from the first step that didn't succeed.
+
+    Succeeded steps preceding the resumption point are marked as reused:
+    they keep their status and outputs and are not run again.
+    Every other step is reset, including the ones skipped
+    because a need listed after them had failed.
+    The new run gets a fresh uuid and is linked to its parent run.
+    */
+    pub fn resume(&self, from: Option<&str>) -> Result<Pipeline> {
+        let index = match from {
+            Some(name) => self
+                .steps
+                .iter()
+                .position(|e| e.get_names().contains(&name.to_owned()))
+                .ok_or_else(|| self.unknown_step(name))?,
+            None => self
+                .steps
+                .iter()
+                .position(|e| {
+                    e.get_status() != Some(Status::Succeeded)
+                        && e.get_status() != Some(Status::Skipped)
+                })
+                .ok_or_else(|| {
+                    let message = format!(
+                        "Pipeline run {} has no failed step to retry from",
+                        self.uuid
+                    );
+                    Error::msg(message)
+                })?,
+        };
+
+        let mut pipeline = self.to_owned();
+        pipeline.uuid = Uuid::new_v4();
+        pipeline.parent = Some(self.uuid);
+        pipeline.event = None;
+        pipeline.status = None;
+        pipeline.duration = None;
+        pipeline.usage = None;
+        for (i, step) in pipeline.steps.iter_mut().enumerate() {
+            if i < index && step.get_status() == Some(Status::Succeeded) {
+                step.reuse();
+            } else {
+                step.reset();
+            }
+        }
+        if let Some(fallback) = &mut pipeline.fallback {
+            fallback.reset();
+        }
+        Ok(pipeline)
+    }
+}
+
+impl StepOrParallel {
+    fn reuse(&mut self) {
+        match self {
+            StepOrParallel::Step(res) => res.reused = Some(true),
+            StepOrParallel::Parallel(res) => {
+                for step in &mut res.steps {
+                    step.reused = Some(true);
+                }
+            }
+        }
+    }
+    fn reset(&mut self) {
+        match self {
+            StepOrParallel::Step(res) => res.reset(),
+            StepOrParallel::Parallel(res) => res.reset(),
+        }
+    }
+}
+
+impl Parallel {
+    fn reset(&mut self) {
+        self.status = None;
+        self.duration = None;
+        for step in &mut self.steps {
+            step.reset();
+        }
+        if let Some(fallback) = &mut self.fallback {
+            fallback.reset();
+        }
+    }
+}
+
+impl Step {
+    /**
+    Drop the previous run results and retry attempts,
+    so that the step can be run again.
+    */
+    fn reset(&mut self) {
+        self.status = None;
+        self.duration = None;
+        self.usage = None;
+        self.reused = None;
+        self.skipped = None;
+        self.commands = self
+            .commands
+            .iter()
+            .filter(|e| e.attempt.unwrap_or(1) == 1)
+            .map(|e| {
+                let mut command = Command::new(&e.get_template().unwrap_or_default());
+                if let Some(env) = &e.process.env {
+                    command.process.envs(env);
+                }
+                command
+            })
+            .collect();
+        for output in self.outputs.iter_mut().flat_map(|e| e.values_mut()) {
+            output.value = None;
+        }
+        if let Some(fallback) = &mut self.fallback {
+            fallback.reset();
+        }
+    }
+}
+
+impl Fallback {
+    fn reset(&mut self) {
+        let fallbacks = [
+            &mut self.on_started,
+            &mut self.on_failure,
+            &mut self.on_success,
+            &mut self.on_abortion,
+        ];
+        for steps in fallbacks.into_iter().flatten() {
+            for step in steps {
+                step.reset();
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+// Types
+use super::PtrWrapper;
+use crate::types::{Mode, Pipeline, Skip, StepOrParallel};
+// Error Handling
+use miette::{Report, Result};
+// Traits
+use pipelight_exec::{Statuable, Status};
+// Parallelism
+use rayon::Scope;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+impl Pipeline {
+    /**
+    Whether some steps declare dependencies,
+    in which case the pipeline is run as a graph.
+    */
+    pub fn has_needs(&self) -> bool {
+        self.steps.iter().any(|e| !e.get_needs().is_empty())
+    }
+    /**
+    Return the pipeline status after a graph run.
+    The first blocking failure wins, and steps left unrun mean failure.
+    */
+    fn get_graph_status(&self) -> Option<Status> {
+        if let Some(step) = self.steps.iter().find(|e| e.is_blocking()) {
+            step.get_status()
+        } else if self.steps.iter().any(|e| e.get_status().is_none()) {
+            Some(Status::Failed)
+        } else {
+            Some(Status::Succeeded)
+        }
+    }
+}
+
+impl StepOrParallel {
+    /**
+    A step that didn't succeed and whose mode doesn't allow to go on.
+    */
+    fn is_blocking(&self) -> bool {
+        let status = self.get_status();
+        let mode = self.get_mode();
+        status.is_some()
+            && status != Some(Status::Succeeded)
+            && status != Some(Status::Skipped)
+            && (mode.is_none() || mode == Some(Mode::StopOnFailure))
+    }
+}
+
+/**
+The scheduling state of a graph run, shared by the steps threads.
+*/
+struct Graph {
+    // The steps dependencies, by node index
+    needs: Vec<Vec<usize>>,
+    // Whether a step has been started (or skipped)
+    started: Vec<bool>,
+    // Whether a step has been skipped because of a need
+    skipped: Vec<bool>,
+    // Whether a finished step has succeeded
+    succeeded: Vec<Option<bool>>,
+    // Whether a blocking failure stops the run
+    blocked: bool,
+    error: Option<Report>,
+}
+impl Graph {
+    /**
+    Return the steps whose needs have all succeeded, and mark them as started.
+
+    A step can only rely on succeeded steps, or on steps left out from the command line.
+    Those with a need that has failed or has been skipped
+    are skipped along with their own dependents.
+    */
+    fn take_ready(&mut self) -> Vec<usize> {
+        let mut ready = vec![];
+        if self.blocked {
+            return ready;
+        }
+        loop {
+            let mut changed = false;
+            for i in 0..self.needs.len() {
+                if self.started[i] {
+                    continue;
+                }
+                let needs: Vec<Option<bool>> =
+                    self.needs[i].iter().map(|e| self.succeeded[*e]).collect();
+                if needs.contains(&Some(false)) {
+                    self.skipped[i] = true;
+                    self.started[i] = true;
+                    self.succeeded[i] = Some(false);
+                    changed = true;
+                } else if needs.iter().all(|e| *e == Some(true)) {
+                    self.started[i] = true;
+                    ready.push(i);
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        ready
+    }
+    /**
+    Record a finished step result.
+    */
+    fn finish(&mut self, step: &StepOrParallel, i: usize, res: Result<()>) {
+        self.succeeded[i] = Some(step.get_status() == Some(Status::Succeeded));
+        if step.is_blocking() {
+            self.blocked = true;
+        }
+        if let Err(err) = res {
+            self.blocked = true;
+            self.error.get_or_insert(err);
+        }
+    }
+}
+
+/**
+Run the pipeline steps as a directed acyclic graph.
+
+Every step is started on the rayon pool as soon as its own needs have succeeded,
+until a step fails or no more step can be started.
+*/
+pub(super) unsafe fn run_graph(ptr: *mut Pipeline) -> Result<()> {
+    // Map every step name to its node index
+    let mut indexes: HashMap<String, usize> = HashMap::new();
+    for (i, step) in (*ptr).steps.iter().enumerate() {
+        for name in step.get_names() {
+            indexes.insert(name, i);
+        }
+    }
+    let steps = &(*ptr).steps;
+    let graph = Graph {
+        // Node dependencies
+        needs: steps
+            .iter()
+            .map(|e| {
+                e.get_needs()
+                    .iter()
+                    .filter_map(|name| indexes.get(name).copied())
+                    .collect()
+            })
+            .collect(),
+        // Steps reused from a parent run are already done,
+        // and steps left out from the command line are deemed satisfied.
+        started: steps
+            .iter()
+            .map(|e| e.is_reused() || e.is_excluded())
+            .collect(),
+        skipped: vec![false; steps.len()],
+        succeeded: steps
+            .iter()
+            .map(|e| {
+                if e.is_excluded() {
+                    Some(true)
+                } else if e.is_reused() {
+                    Some(e.get_status() == Some(Status::Succeeded))
+                } else {
+                    None
+                }
+            })
+            .collect(),
+        blocked: false,
+        error: None,
+    };
+    let graph = Mutex::new(graph);
+    let ptr_wrapper = PtrWrapper(ptr);
+    let steps = StepsPtr((*ptr).steps.as_mut_ptr());
+    rayon::scope(|scope| schedule(scope, &graph, ptr_wrapper, steps));
+
+    let graph = graph.into_inner().unwrap();
+    for (i, step) in (*ptr).steps.iter_mut().enumerate() {
+        if graph.skipped[i] {
+            step.skip(Skip::NeedFailed);
+        }
+    }
+    if let Some(err) = graph.error {
+        return Err(err);
+    }
+    (*ptr).set_status((*ptr).get_graph_status());
+    Ok(())
+}
+
+/**
+A pointer to the pipeline steps, shared by the steps threads.
+
+Every thread only borrows the step it runs,
+so that no reference to the whole steps slice exists while steps are running.
+*/
+#[derive(Debug, Clone, Copy)]
+struct StepsPtr(*mut StepOrParallel);
+unsafe impl Sync for StepsPtr {}
+unsafe impl Send for StepsPtr {}
+impl StepsPtr {
+    /**
+    Borrow the step at the given index.
+
+    # Safety
+    The index must be in bounds, and the step borrowed by a single thread at a time.
+    */
+    unsafe fn get<'a>(self, i: usize) -> &'a mut StepOrParallel {
+        &mut *self.0.add(i)
+    }
+}
+
+/**
+Start the steps that are ready to run.
+Every finished step schedules the steps that were waiting for it.
+*/
+fn schedule<'a>(scope: &Scope<'a>, graph: &'a Mutex<Graph>, ptr: PtrWrapper, steps: StepsPtr) {
+    let ready = graph.lock().unwrap().take_ready();
+    for i in ready {
+        scope.spawn(move |scope| {
+            // A step is started once, so it is only borrowed by this thread.
+            let step = unsafe { steps.get(i) };
+            let res = step.unsafe_run(ptr);
+            graph.lock().unwrap().finish(step, i, res);
+            schedule(scope, graph, ptr, steps);
+        });
+    }
+}
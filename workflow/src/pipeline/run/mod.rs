@@ -1,14 +1,25 @@
 // Types
-use crate::types::{Command, Event, Mode, Parallel, Pipeline, Step, StepOrParallel};
+use crate::types::{Command, Event, Logs, Mode, Parallel, Pipeline, Skip, Step, StepOrParallel};
 use pipelight_exec::dates::Duration;
 // Error Handling
-use miette::Result;
+use log::error;
+use miette::{Report, Result};
+// Conditions
+use crate::context::Context;
+use cast::Expr;
 // Traits
-use pipelight_exec::{Statuable, Status};
+use pipelight_exec::{Statuable, Status, Usage};
 // Global var
+use crate::globals::STREAM;
 use once_cell::sync::Lazy;
 // Parallelism
 use rayon::prelude::*;
+// Timeout and retry
+use std::{thread, time};
+// Process tagging
+use super::stop::RUN_UUID_ENV;
+// Step dependencies
+mod dag;
 // Tests
 mod test;
 
@@ -53,12 +64,21 @@ impl Pipeline {
             (*ptr).set_status(Some(Status::Running));
             (*ptr).log()?;
 
-            for step in &mut (*ptr).steps {
-                step.run(ptr)?;
-                if (step.get_status() != Some(Status::Succeeded))
-                    && (step.get_mode().is_none() || step.get_mode() == Some(Mode::StopOnFailure))
-                {
-                    break;
+            if (*ptr).has_needs() {
+                dag::run_graph(ptr)?;
+            } else {
+                for step in &mut (*ptr).steps {
+                    if step.is_reused() {
+                        continue;
+                    }
+                    step.run(ptr)?;
+                    if (step.get_status() != Some(Status::Succeeded)
+                        && step.get_status() != Some(Status::Skipped))
+                        && (step.get_mode().is_none()
+                            || step.get_mode() == Some(Mode::StopOnFailure))
+                    {
+                        break;
+                    }
                 }
             }
         }
@@ -69,21 +89,43 @@ impl Pipeline {
             (*ptr).duration = Some(d.clone());
         }
 
-        // Set pipeline status to last Step status
+        // Resource usage
         unsafe {
-            let last_step = (*ptr).steps.last().unwrap();
-            if last_step.get_status().is_some() {
-                if last_step.get_mode() == Some(Mode::JumpNextOnFailure) {
-                    if last_step.get_status() == Some(Status::Failed) {
-                        (*ptr).set_status(Some(Status::Succeeded))
+            let usages: Vec<Usage> = (*ptr).steps.iter().filter_map(|e| e.get_usage()).collect();
+            (*ptr).usage = Usage::aggregate(&usages);
+        }
+
+        // Set pipeline status to last run Step status
+        // (a graph run sets it on its own)
+        unsafe {
+            if !(*ptr).has_needs() {
+                let last_step = (*ptr)
+                    .steps
+                    .iter()
+                    .rev()
+                    .find(|e| e.get_status() != Some(Status::Skipped));
+                if let Some(last_step) = last_step.filter(|e| e.get_status().is_some()) {
+                    if last_step.get_mode() == Some(Mode::JumpNextOnFailure) {
+                        if last_step.get_status() == Some(Status::Failed) {
+                            (*ptr).set_status(Some(Status::Succeeded))
+                        } else {
+                            (*ptr).set_status(last_step.get_status())
+                        }
                     } else {
                         (*ptr).set_status(last_step.get_status())
                     }
+                } else if last_step.is_none() {
+                    // Every step has been skipped
+                    (*ptr).set_status(Some(Status::Succeeded))
+                } else if (*ptr)
+                    .steps
+                    .iter()
+                    .any(|e| e.get_status() == Some(Status::TimedOut))
+                {
+                    (*ptr).set_status(Some(Status::TimedOut))
                 } else {
-                    (*ptr).set_status(last_step.get_status())
+                    (*ptr).set_status(Some(Status::Failed))
                 }
-            } else {
-                (*ptr).set_status(Some(Status::Failed))
             }
             (*ptr).log()?;
         }
@@ -92,7 +134,10 @@ impl Pipeline {
         unsafe {
             if (*ptr).fallback.is_some() {
                 let fallback = &mut (*ptr).fallback.as_mut().unwrap();
-                if (*ptr).status == Some(Status::Failed) && fallback.on_failure.is_some() {
+                if ((*ptr).status == Some(Status::Failed)
+                    || (*ptr).status == Some(Status::TimedOut))
+                    && fallback.on_failure.is_some()
+                {
                     // let steps = (*ptr).on_failure.as_mut().unwrap();
                     for step in fallback.on_failure.as_mut().unwrap() {
                         step.run(ptr)?;
@@ -120,11 +165,17 @@ impl Pipeline {
             let global_pipe = &mut (*ptr);
             *self = global_pipe.to_owned();
         }
+        // Apply the logs retention policy
+        Logs::prune_with_config();
         Ok(())
     }
 }
 
 impl StepOrParallel {
+    fn unsafe_run(&mut self, ptr: PtrWrapper) -> Result<()> {
+        let ptr = ptr.0;
+        self.run(ptr)
+    }
     fn run(&mut self, ptr: *mut Pipeline) -> Result<()> {
         match self {
             StepOrParallel::Step(res) => res.run(ptr),
@@ -149,14 +200,20 @@ impl Parallel {
             .for_each(|e| e.unsafe_run(ptr_wrapper).unwrap());
 
         // Set parallel global status
+        // Skipped steps are ignored
         let steps_res: Vec<Status> = self
             .steps
             .iter()
             .map(|e| e.clone().status.unwrap())
+            .filter(|e| e != &Status::Skipped)
             .collect();
 
-        if steps_res.contains(&Status::Failed) {
+        if steps_res.is_empty() {
+            self.set_status(Some(Status::Skipped));
+        } else if steps_res.contains(&Status::Failed) {
             self.set_status(Some(Status::Failed));
+        } else if steps_res.contains(&Status::TimedOut) {
+            self.set_status(Some(Status::TimedOut));
         } else if steps_res.contains(&Status::Aborted) {
             self.set_status(Some(Status::Aborted));
         } else {
@@ -184,6 +241,27 @@ impl Step {
         self.run(ptr)
     }
     fn run(&mut self, ptr: *mut Pipeline) -> Result<()> {
+        // Left out from the command line
+        if self.skipped == Some(Skip::Excluded) {
+            return Ok(());
+        }
+        // Variables for the condition and the commands placeholders
+        let mut context = unsafe { Context::new(&*ptr)? };
+        if let Some(env) = self.commands.first().and_then(|e| e.process.env.as_ref()) {
+            context.env(env);
+        }
+        // Condition
+        if let Some(when) = &self.when {
+            let expr = Expr::parse(when).map_err(Report::new)?;
+            if !context.eval(&expr).is_truthy() {
+                self.skip(Skip::ConditionFalse);
+                unsafe {
+                    (*ptr).log()?;
+                }
+                return Ok(());
+            }
+        }
+
         // Options
         let mode = self.get_mode();
         // Duration
@@ -193,21 +271,119 @@ impl Step {
 
         self.set_status(Some(Status::Running));
 
+        // Timeout
+        let started_at = time::Instant::now();
+        let timeout = self.get_timeout();
+        // Compute the remaining time budget from the step and pipeline timeouts.
+        let get_time_left = || -> Result<Option<time::Duration>> {
+            let mut time_left = timeout.map(|e| e.saturating_sub(started_at.elapsed()));
+            unsafe {
+                if let Some(pipeline_time_left) = (*ptr).get_time_left()? {
+                    time_left =
+                        Some(time_left.map_or(pipeline_time_left, |e| e.min(pipeline_time_left)));
+                }
+            }
+            Ok(time_left)
+        };
+
+        // Retry
+        let retry = self.get_retry();
+        let allowed_exit_codes = self.get_allowed_exit_codes();
+
+        // Resource limits and terminal
+        let limits = self.get_limits();
+        let tty = self.is_tty();
+
+        // Outputs
+        let output_file = self.set_output_file()?;
+
         // Run commands
-        for command in &mut self.commands {
-            command.run(ptr)?;
+        let mut index = 0;
+        while index < self.commands.len() {
+            let command = &mut self.commands[index];
+            let time_left = get_time_left()?;
+            if let Some(time_left) = time_left {
+                if time_left.is_zero() {
+                    command.set_status(Some(Status::TimedOut));
+                    break;
+                }
+                command.process.timeout(&time_left);
+            }
 
-            if (command.get_status().is_none() || command.get_status() != Some(Status::Succeeded))
-                && (mode.is_none() || mode != Some(Mode::ContinueOnFailure))
+            // Working directory and shell
+            if let Some(workdir) = &self.workdir {
+                command.process.cwd(workdir);
+            }
+            if let Some([program, args @ ..]) = self.shell.as_deref() {
+                command.process.shell(program, args);
+            }
+            if let Some(limits) = &limits {
+                command.process.limits(limits);
+            }
+            if tty {
+                command.process.pty();
+            }
+
+            command.run(ptr, &self.name, &context)?;
+
+            if let Some(diag) = command.get_limit_error(&self.name) {
+                error!("{:?}", Report::new(diag));
+            }
+
+            // Tolerate allowed exit codes
+            if command.get_status() == Some(Status::Failed)
+                && command
+                    .process
+                    .state
+                    .exit_code
+                    .is_some_and(|e| allowed_exit_codes.contains(&e))
             {
+                command.set_status(Some(Status::Succeeded));
+            }
+
+            let failed =
+                command.get_status().is_none() || command.get_status() != Some(Status::Succeeded);
+
+            // Keep the failed attempt and queue a fresh copy of the command.
+            if failed {
+                if let Some(retry) = &retry {
+                    let attempt = command.attempt.unwrap_or(1);
+                    let delay = retry.get_delay(attempt + 1);
+                    // Don't wait for an attempt the time budget can't afford.
+                    let can_wait = get_time_left()?.is_none_or(|e| delay < e);
+                    if can_wait && retry.should_retry(attempt, command.process.state.exit_code) {
+                        command.attempt = Some(attempt);
+                        let mut next = Command::new(&command.get_template().unwrap());
+                        if let Some(env) = &command.process.env {
+                            next.process.envs(env);
+                        }
+                        next.attempt = Some(attempt + 1);
+                        thread::sleep(delay);
+                        self.commands.insert(index + 1, next);
+                        index += 1;
+                        continue;
+                    }
+                }
+            }
+
+            if failed && (mode.is_none() || mode != Some(Mode::ContinueOnFailure)) {
                 break;
             }
+            index += 1;
         }
 
+        self.capture_outputs(&output_file)?;
+
         // Set global status after run
         let final_status = &self.commands.last().unwrap().get_status();
         if final_status.is_some() {
             self.status = final_status.clone();
+        } else if self
+            .commands
+            .iter()
+            .any(|e| e.get_status() == Some(Status::TimedOut))
+        {
+            self.set_status(Some(Status::TimedOut))
         } else {
             self.set_status(Some(Status::Failed))
         }
@@ -216,13 +392,22 @@ impl Step {
         d.stop()?;
         self.duration = Some(d);
 
+        // Resource usage
+        self.usage = Usage::aggregate(
+            self.commands
+                .iter()
+                .filter_map(|e| e.process.state.usage.as_ref()),
+        );
+
         unsafe {
             (*ptr).log()?;
         }
         // Execute post-run steps
         if self.fallback.is_some() {
             let fallback = &mut self.fallback.as_mut().unwrap();
-            if self.status == Some(Status::Failed) && fallback.on_failure.is_some() {
+            if (self.status == Some(Status::Failed) || self.status == Some(Status::TimedOut))
+                && fallback.on_failure.is_some()
+            {
                 for step in fallback.on_failure.as_mut().unwrap() {
                     step.run(ptr)?;
                 }
@@ -246,19 +431,37 @@ impl Step {
 }
 
 impl Command {
-    fn run(&mut self, ptr: *mut Pipeline) -> Result<()> {
+    fn run(&mut self, ptr: *mut Pipeline, step_name: &str, context: &Context) -> Result<()> {
         // Duration
         let mut d = Duration::default();
         d.start()?;
         self.duration = Some(d.clone());
 
+        // Resolve placeholders, and keep the command as written
+        if let Some(template) = self.get_template() {
+            let stdin = context.interpolate(&template);
+            if stdin != template {
+                self.template = Some(template);
+            }
+            self.process.io.stdin = Some(stdin);
+        }
+
         self.set_status(Some(Status::Running));
         unsafe {
             (*ptr).log()?;
         }
 
         // Run process
-        let res = self.process.term().fs().run();
+        // Tag the process tree with the run uuid
+        let uuid = unsafe { (*ptr).uuid };
+        self.process.env(RUN_UUID_ENV, &uuid.to_string());
+
+        let max_output = unsafe { (*ptr).get_max_output() };
+        self.process.term().fs().max_output(max_output);
+        if *STREAM.lock().unwrap() {
+            self.process.stream(step_name);
+        }
+        let res = self.process.run();
         let _ = match res {
             Ok(_) => Ok(()),
             Err(e) => {
@@ -1,7 +1,14 @@
 #[cfg(test)]
 mod test {
-    use crate::types::{Command, Parallel, Pipeline, Step, StepOrParallel};
+    use crate::types::{
+        Command, Limits, Mode, Node, Parallel, Pipeline, PipelineOpts, Retry, Skip, Step, StepOpts,
+        StepOrParallel, StepOutput,
+    };
+    use chrono::{DateTime, Local};
+    use log::LevelFilter;
     use miette::Result;
+    use pipelight_exec::{Limit, Statuable, Status};
+    use std::collections::BTreeMap;
 
     #[test]
     fn can_run() {
@@ -55,4 +62,695 @@ mod test {
         println!("{:#?}", p);
         Ok(())
     }
+    #[test]
+    fn run_timeout() -> Result<()> {
+        let mut p = Pipeline {
+            name: "timeout".to_owned(),
+            steps: vec![StepOrParallel::Step(Step {
+                name: "test".to_owned(),
+                commands: vec![Command::new("sleep 30"), Command::new("pwd")],
+                options: Some(StepOpts {
+                    timeout: Some("PT1S".to_owned()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.steps[0].get_status(), Some(Status::TimedOut));
+        assert_eq!(p.get_status(), Some(Status::TimedOut));
+        Ok(())
+    }
+    #[test]
+    fn run_retry() -> Result<()> {
+        let step = |exit_codes: Option<Vec<i32>>| {
+            StepOrParallel::Step(Step {
+                name: "test".to_owned(),
+                commands: vec![Command::new("exit 3")],
+                options: Some(StepOpts {
+                    retry: Some(Retry {
+                        attempts: 3,
+                        backoff: Some("PT0.1S".to_owned()),
+                        exit_codes,
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        };
+        let mut p = Pipeline {
+            name: "retry".to_owned(),
+            steps: vec![step(None)],
+            ..Default::default()
+        };
+        p.run()?;
+        // Every attempt is recorded
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => {
+                assert_eq!(res.commands.len(), 3);
+                assert_eq!(res.commands[2].attempt, Some(3));
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(p.steps[0].get_status(), Some(Status::Failed));
+
+        // Unlisted exit codes are not retried
+        let mut p = Pipeline {
+            name: "retry_exit_codes".to_owned(),
+            steps: vec![step(Some(vec![4]))],
+            ..Default::default()
+        };
+        p.run()?;
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => assert_eq!(res.commands.len(), 1),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+    #[test]
+    fn run_retry_time_budget() -> Result<()> {
+        let mut p = Pipeline {
+            name: "retry_time_budget".to_owned(),
+            steps: vec![StepOrParallel::Step(Step {
+                name: "test".to_owned(),
+                commands: vec![Command::new("exit 3")],
+                options: Some(StepOpts {
+                    timeout: Some("PT1S".to_owned()),
+                    retry: Some(Retry {
+                        attempts: 3,
+                        backoff: Some("PT5S".to_owned()),
+                        exit_codes: None,
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        let started_at = std::time::Instant::now();
+        p.run()?;
+        // The backoff exceeds the step timeout, so no retry is attempted.
+        assert!(started_at.elapsed() < std::time::Duration::from_secs(1));
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => assert_eq!(res.commands.len(), 1),
+            _ => unreachable!(),
+        }
+        assert_eq!(p.steps[0].get_status(), Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn run_needs() -> Result<()> {
+        let step = |name: &str, command: &str, needs: Vec<&str>| {
+            StepOrParallel::Step(Step {
+                name: name.to_owned(),
+                commands: vec![Command::new(command)],
+                needs: Some(needs.iter().map(|e| e.to_string()).collect()),
+                ..Default::default()
+            })
+        };
+        let mut p = Pipeline {
+            name: "needs".to_owned(),
+            steps: vec![
+                step("test", "pwd", vec!["build", "lint"]),
+                step("build", "sleep 1", vec![]),
+                step("lint", "sleep 1", vec![]),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.steps[0].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+
+        // Dependents of a failed step are never run.
+        let mut p = Pipeline {
+            name: "needs_failure".to_owned(),
+            steps: vec![
+                step("build", "exit 1", vec![]),
+                step("lint", "pwd", vec![]),
+                step("test", "pwd", vec!["build"]),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.steps[1].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.steps[2].get_status(), None);
+        assert_eq!(p.get_status(), Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn run_needs_eagerly() -> Result<()> {
+        let step = |name: &str, command: &str, needs: Vec<&str>| {
+            StepOrParallel::Step(Step {
+                name: name.to_owned(),
+                commands: vec![Command::new(command)],
+                needs: Some(needs.iter().map(|e| e.to_string()).collect()),
+                ..Default::default()
+            })
+        };
+        let mut p = Pipeline {
+            name: "needs_eagerly".to_owned(),
+            steps: vec![
+                step("slow", "sleep 2", vec![]),
+                step("build", "pwd", vec![]),
+                step("test", "pwd", vec!["build"]),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        // The build -> test chain doesn't wait for the unrelated slow step.
+        let ended_at = |step: &StepOrParallel| match step {
+            StepOrParallel::Step(res) => res
+                .duration
+                .clone()
+                .and_then(|e| e.ended_at)
+                .unwrap()
+                .parse::<DateTime<Local>>()
+                .unwrap(),
+            _ => unreachable!(),
+        };
+        assert!(ended_at(&p.steps[2]) < ended_at(&p.steps[0]));
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_needs_unsatisfied() -> Result<()> {
+        let step = |name: &str, command: &str, needs: Vec<&str>| {
+            StepOrParallel::Step(Step {
+                name: name.to_owned(),
+                commands: vec![Command::new(command)],
+                needs: Some(needs.iter().map(|e| e.to_string()).collect()),
+                options: Some(StepOpts {
+                    mode: Some(Mode::ContinueOnFailure),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        };
+        let mut p = Pipeline {
+            name: "needs_unsatisfied".to_owned(),
+            steps: vec![
+                step("test", "exit 1", vec![]),
+                step("deploy", "pwd", vec!["test"]),
+                step("notify", "pwd", vec!["deploy"]),
+                step("lint", "pwd", vec![]),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        // Dependents of a step that didn't succeed are skipped, even on a tolerated failure.
+        let statuses: Vec<Option<Status>> = p.steps.iter().map(|e| e.get_status()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                Some(Status::Failed),
+                Some(Status::Skipped),
+                Some(Status::Skipped),
+                Some(Status::Succeeded)
+            ]
+        );
+        match &p.steps[1] {
+            StepOrParallel::Step(res) => {
+                assert_eq!(res.commands[0].get_status(), None);
+                assert_eq!(res.skipped, Some(Skip::NeedFailed));
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+    #[test]
+    fn run_when() -> Result<()> {
+        let step = |name: &str, when: &str| {
+            StepOrParallel::Step(Step {
+                name: name.to_owned(),
+                commands: vec![Command::new("pwd")],
+                when: Some(when.to_owned()),
+                ..Default::default()
+            })
+        };
+        let mut p = Pipeline {
+            name: "when".to_owned(),
+            steps: vec![
+                step("build", "pipeline.name == 'when'"),
+                step("deploy", "pipeline.name != 'when'"),
+                step("notify", "steps.build.status == 'succeeded'"),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.steps[0].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.steps[1].get_status(), Some(Status::Skipped));
+        match &p.steps[1] {
+            StepOrParallel::Step(res) => assert_eq!(res.skipped, Some(Skip::ConditionFalse)),
+            _ => unreachable!(),
+        }
+        assert_eq!(p.steps[2].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_interpolation() -> Result<()> {
+        let mut p = Pipeline {
+            name: "interpolation".to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Step {
+                    name: "build".to_owned(),
+                    commands: vec![Command::new("echo ${{ pipeline.name }}")],
+                    ..Default::default()
+                }),
+                StepOrParallel::Step(Step {
+                    name: "notify".to_owned(),
+                    commands: vec![Command::new("echo ${{ steps.build.status }}")],
+                    ..Default::default()
+                }),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        let stdout = |step: &StepOrParallel| match step {
+            StepOrParallel::Step(res) => res.commands[0].process.io.stdout.clone(),
+            _ => unreachable!(),
+        };
+        assert_eq!(stdout(&p.steps[0]), Some("interpolation\n".to_owned()));
+        assert_eq!(stdout(&p.steps[1]), Some("succeeded\n".to_owned()));
+        // The command as written is kept apart from the resolved one
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => {
+                let command = &res.commands[0];
+                assert_eq!(
+                    command.get_template(),
+                    Some("echo ${{ pipeline.name }}".to_owned())
+                );
+                assert_eq!(
+                    command.process.io.stdin,
+                    Some("echo interpolation".to_owned())
+                );
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+    #[test]
+    fn run_outputs() -> Result<()> {
+        let mut p = Pipeline {
+            name: "outputs".to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Step {
+                    name: "build".to_owned(),
+                    commands: vec![
+                        Command::new("echo version: 1.2.3"),
+                        Command::new("echo sha=abc123 >> $PIPELIGHT_OUTPUT"),
+                    ],
+                    outputs: Some(BTreeMap::from([(
+                        "version".to_owned(),
+                        StepOutput {
+                            source: Some("version: (.*)".to_owned()),
+                            value: None,
+                        },
+                    )])),
+                    ..Default::default()
+                }),
+                StepOrParallel::Step(Step {
+                    name: "release".to_owned(),
+                    commands: vec![Command::new(
+                        "echo ${{ steps.build.outputs.version }}-${{ steps.build.outputs.sha }}",
+                    )],
+                    ..Default::default()
+                }),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        match &p.steps[1] {
+            StepOrParallel::Step(res) => assert_eq!(
+                res.commands[0].process.io.stdout,
+                Some("1.2.3-abc123\n".to_owned())
+            ),
+            _ => unreachable!(),
+        }
+        // The output file is kept with the run commands outputs
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => {
+                let (stdout_path, _) = res.commands[0].process.io.get_paths();
+                let path = std::path::Path::new(&stdout_path).with_file_name("output");
+                assert_eq!(std::fs::read_to_string(path).unwrap(), "sha=abc123\n");
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+    #[test]
+    fn run_resume() -> Result<()> {
+        let step = |name: &str, command: &str| {
+            StepOrParallel::Step(Step {
+                name: name.to_owned(),
+                commands: vec![Command::new(command)],
+                ..Default::default()
+            })
+        };
+        let mut p = Pipeline {
+            name: "resume".to_owned(),
+            steps: vec![
+                step("build", "sleep 1"),
+                step("test", "exit 1"),
+                step("deploy", "pwd"),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.get_status(), Some(Status::Failed));
+
+        // Resume from the failing step, the previous ones are not run again.
+        let mut resumed = p.resume(None)?;
+        assert_ne!(resumed.uuid, p.uuid);
+        assert_eq!(resumed.parent, Some(p.uuid));
+        assert!(resumed.steps[0].is_reused());
+        assert_eq!(resumed.steps[1].get_status(), None);
+        // Both runs share the test process pid, rename to bypass the homologous guard.
+        resumed.name = "resume_retry".to_owned();
+        resumed.run()?;
+        match (&p.steps[0], &resumed.steps[0]) {
+            (StepOrParallel::Step(before), StepOrParallel::Step(after)) => {
+                assert_eq!(before.duration, after.duration)
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(resumed.steps[1].get_status(), Some(Status::Failed));
+        assert_eq!(resumed.get_status(), Some(Status::Failed));
+
+        // Resume from an explicit step.
+        let resumed = p.resume(Some("build"))?;
+        assert!(!resumed.steps[0].is_reused());
+        assert!(p.resume(Some("unknown")).is_err());
+        Ok(())
+    }
+    #[test]
+    fn run_resume_needs() -> Result<()> {
+        let step = |name: &str, command: &str, needs: Vec<&str>| {
+            StepOrParallel::Step(Step {
+                name: name.to_owned(),
+                commands: vec![Command::new(command)],
+                needs: Some(needs.iter().map(|e| e.to_string()).collect()),
+                options: Some(StepOpts {
+                    mode: Some(Mode::ContinueOnFailure),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        };
+        let mut p = Pipeline {
+            name: "resume_needs".to_owned(),
+            steps: vec![
+                step("deploy", "pwd", vec!["test"]),
+                step("build", "pwd", vec![]),
+                step("test", "exit 1", vec!["build"]),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.steps[0].get_status(), Some(Status::Skipped));
+
+        // A step skipped because of a need listed after it is run again.
+        let resumed = p.resume(None)?;
+        assert!(!resumed.steps[0].is_reused());
+        assert_eq!(resumed.steps[0].get_status(), None);
+        assert!(resumed.steps[1].is_reused());
+        assert!(!resumed.steps[2].is_reused());
+        Ok(())
+    }
+    #[test]
+    fn run_selection() -> Result<()> {
+        let step = |name: &str| {
+            StepOrParallel::Step(Step {
+                name: name.to_owned(),
+                commands: vec![Command::new("pwd")],
+                ..Default::default()
+            })
+        };
+        let mut p = Pipeline {
+            name: "selection".to_owned(),
+            steps: vec![step("lint"), step("build"), step("test"), step("deploy")],
+            ..Default::default()
+        };
+        assert!(p
+            .select_steps(&["unknown".to_owned()], &[], None, None)
+            .is_err());
+        assert!(p
+            .select_steps(&[], &[], Some("test"), Some("build"))
+            .is_err());
+        p.select_steps(&[], &["test".to_owned()], Some("build"), Some("test"))?;
+        p.run()?;
+        let statuses: Vec<Option<Status>> = p.steps.iter().map(|e| e.get_status()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                Some(Status::Skipped),
+                Some(Status::Succeeded),
+                Some(Status::Skipped),
+                Some(Status::Skipped)
+            ]
+        );
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_selection_needs() -> Result<()> {
+        let step = |name: &str, needs: Vec<&str>| {
+            StepOrParallel::Step(Step {
+                name: name.to_owned(),
+                commands: vec![Command::new("pwd")],
+                needs: Some(needs.iter().map(|e| e.to_string()).collect()),
+                ..Default::default()
+            })
+        };
+        let mut p = Pipeline {
+            name: "selection_needs".to_owned(),
+            steps: vec![
+                step("build", vec![]),
+                step("test", vec!["build"]),
+                step("deploy", vec!["test"]),
+            ],
+            ..Default::default()
+        };
+        // The needs left out of the run don't prevent the selected step from running.
+        p.select_steps(&["deploy".to_owned()], &[], None, None)?;
+        p.run()?;
+        let statuses: Vec<Option<Status>> = p.steps.iter().map(|e| e.get_status()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                Some(Status::Skipped),
+                Some(Status::Skipped),
+                Some(Status::Succeeded)
+            ]
+        );
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_allowed_exit_codes() -> Result<()> {
+        let mut p = Pipeline {
+            name: "allowed_exit_codes".to_owned(),
+            steps: vec![StepOrParallel::Step(Step {
+                name: "lint".to_owned(),
+                commands: vec![Command::new("exit 3")],
+                options: Some(StepOpts {
+                    allowed_exit_codes: Some(vec![0, 3]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        p.run()?;
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => {
+                assert_eq!(res.commands[0].process.state.exit_code, Some(3))
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_workdir_and_shell() -> Result<()> {
+        let mut p = Pipeline {
+            name: "workdir_and_shell".to_owned(),
+            steps: vec![StepOrParallel::Step(Step {
+                name: "build".to_owned(),
+                commands: vec![Command::new("pwd; false; echo unreachable")],
+                workdir: Some("/tmp".to_owned()),
+                shell: Some(vec!["sh".to_owned(), "-e".to_owned(), "-c".to_owned()]),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        p.run()?;
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => {
+                assert_eq!(res.commands[0].process.io.stdout, Some("/tmp\n".to_owned()))
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(p.get_status(), Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn run_usage() -> Result<()> {
+        let mut p = Pipeline {
+            name: "usage".to_owned(),
+            steps: vec![StepOrParallel::Step(Step {
+                name: "build".to_owned(),
+                commands: vec![Command::new("true"), Command::new("true")],
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        p.run()?;
+        let step_usage = match &p.steps[0] {
+            StepOrParallel::Step(res) => res.usage.clone().unwrap(),
+            _ => unreachable!(),
+        };
+        assert!(step_usage.max_rss > 0);
+        assert_eq!(p.usage, Some(step_usage));
+        Ok(())
+    }
+    #[test]
+    fn run_limits() -> Result<()> {
+        let mut p = Pipeline {
+            name: "limits".to_owned(),
+            steps: vec![StepOrParallel::Step(Step {
+                name: "spin".to_owned(),
+                commands: vec![Command::new("while :; do :; done")],
+                options: Some(StepOpts {
+                    limits: Some(Limits {
+                        cpu: Some(1),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        p.run()?;
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => {
+                assert_eq!(res.commands[0].process.state.exceeded, Some(Limit::Cpu));
+                let node = Node::from(res);
+                assert!(node
+                    .children
+                    .unwrap()
+                    .iter()
+                    .any(|e| e.level == LevelFilter::Error
+                        && e.value
+                            .as_ref()
+                            .is_some_and(|e| e.contains("cpu time limit exceeded"))));
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(p.get_status(), Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn run_binary_output() -> Result<()> {
+        let mut p = Pipeline {
+            name: "binary_output".to_owned(),
+            steps: vec![StepOrParallel::Step(Step {
+                name: "cat".to_owned(),
+                commands: vec![Command::new("printf '\\377\\000'; seq 1 100000")],
+                ..Default::default()
+            })],
+            options: Some(PipelineOpts {
+                max_output: Some(1024),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        p.run()?;
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => {
+                let io = &res.commands[0].process.io;
+                assert_eq!(io.is_binary, Some(true));
+                assert!(io.stdout.clone().unwrap().len() < 2048);
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_truncated_outputs() -> Result<()> {
+        let mut p = Pipeline {
+            name: "truncated_outputs".to_owned(),
+            steps: vec![StepOrParallel::Step(Step {
+                name: "build".to_owned(),
+                commands: vec![Command::new(
+                    "seq 1 10000; echo version: 1.2.3; seq 1 10000",
+                )],
+                outputs: Some(BTreeMap::from([(
+                    "version".to_owned(),
+                    StepOutput {
+                        source: Some("version: (.*)".to_owned()),
+                        value: None,
+                    },
+                )])),
+                ..Default::default()
+            })],
+            options: Some(PipelineOpts {
+                max_output: Some(1024),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        p.run()?;
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => {
+                let outputs = res.outputs.clone().unwrap();
+                assert_eq!(outputs["version"].value, Some("1.2.3".to_owned()));
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+    #[test]
+    fn run_tty() -> Result<()> {
+        let mut p = Pipeline {
+            name: "tty".to_owned(),
+            steps: vec![StepOrParallel::Step(Step {
+                name: "check".to_owned(),
+                commands: vec![Command::new("test -t 1")],
+                options: Some(StepOpts {
+                    tty: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_uuid_env() -> Result<()> {
+        let mut p = Pipeline {
+            name: "run_uuid_env".to_owned(),
+            steps: vec![StepOrParallel::Step(Step {
+                name: "tag".to_owned(),
+                commands: vec![Command::new("echo $PIPELIGHT_RUN_UUID")],
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        p.run()?;
+        match &p.steps[0] {
+            StepOrParallel::Step(res) => assert_eq!(
+                res.commands[0].process.io.stdout,
+                Some(format!("{}\n", p.uuid))
+            ),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
 }
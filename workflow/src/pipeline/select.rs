@@ -0,0 +1,65 @@
+// Structs
+use crate::types::{Pipeline, Skip, StepOrParallel};
+// Error Handling
+use miette::{miette, Result};
+
+impl Pipeline {
+    /**
+    Restrict the run to a subset of the pipeline steps.
+
+    - only: run only those steps (every step when empty),
+    - skip: never run those steps,
+    - from/until: run the steps in between (included).
+
+    Left out steps are marked as skipped beforehand,
+    so that they are reported as such in the pipeline tree and logs.
+    */
+    pub fn select_steps(
+        &mut self,
+        only: &[String],
+        skip: &[String],
+        from: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<()> {
+        // Guard
+        let names: Vec<String> = self.steps.iter().flat_map(|e| e.get_names()).collect();
+        let args = only.iter().chain(skip).map(|e| e.as_str());
+        for name in args.chain(from).chain(until) {
+            if !names.contains(&name.to_owned()) {
+                return Err(self.unknown_step(name));
+            }
+        }
+
+        let position = |name: &str| {
+            self.steps
+                .iter()
+                .position(|e| e.get_names().contains(&name.to_owned()))
+        };
+        let start = from.and_then(position).unwrap_or(0);
+        let end = until.and_then(position).unwrap_or(usize::MAX);
+        if start > end {
+            return Err(miette!(
+                help = "Swap the --from and --until steps",
+                "The step \"{}\" comes after the step \"{}\"",
+                from.unwrap_or_default(),
+                until.unwrap_or_default()
+            ));
+        }
+
+        for (i, step) in self.steps.iter_mut().enumerate() {
+            let steps = match step {
+                StepOrParallel::Step(res) => vec![res],
+                StepOrParallel::Parallel(res) => res.steps.iter_mut().collect(),
+            };
+            for step in steps {
+                let selected = (start..=end).contains(&i)
+                    && (only.is_empty() || only.contains(&step.name))
+                    && !skip.contains(&step.name);
+                if !selected {
+                    step.skip(Skip::Excluded);
+                }
+            }
+        }
+        Ok(())
+    }
+}
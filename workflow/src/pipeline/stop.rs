@@ -1,21 +1,59 @@
 // Structs
 use crate::types::{Pipeline, Status};
+use pipelight_exec::Finder;
 // Error Handling
-use miette::{IntoDiagnostic, Result};
+use miette::Result;
 // Unix process manipiulation
 use rustix::process::{kill_process_group, Signal};
+use std::time;
+
+/**
+The environment variable holding the uuid of the pipeline run,
+passed to every command so that the processes a run spawned can be found back.
+*/
+pub const RUN_UUID_ENV: &str = "PIPELIGHT_RUN_UUID";
+
+/**
+Delay between the graceful (SIGTERM) and the forced (SIGKILL) termination
+of the processes of a stopped pipeline.
+*/
+const GRACE_PERIOD: time::Duration = time::Duration::from_secs(5);
 
 /**
 Abort process execution
-Kil the process group
+Kill the process group, and every process the run spawned
 */
 impl Pipeline {
     pub fn stop(&mut self) -> Result<()> {
         if self.event.is_some() && self.status == Some(Status::Running) {
-            let _pid = self.clone().event.unwrap().pid.unwrap();
-                let pgid_raw = self.event.clone().unwrap().pgid.unwrap();
-                let pgid = rustix::process::Pid::from_raw(pgid_raw).unwrap();
-                kill_process_group(pgid, Signal::Term).into_diagnostic()?;
+            let pid = self.clone().event.unwrap().pid.unwrap();
+
+            // Search processes before killing any, as orphans lose their parent links.
+            // The pipeline process, its descendants,
+            // and the processes that left the tree but kept the run uuid.
+            let mut processes = Finder::new().pid(&(pid as u32)).search()?;
+            let descendants = Finder::new().descendants(&(pid as u32)).search()?;
+            let tagged = Finder::new()
+                .env(RUN_UUID_ENV, &self.uuid.to_string())
+                .search()?;
+            let mut matches = processes.matches.clone().unwrap_or_default();
+            for process in [descendants.matches, tagged.matches]
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                if !matches.iter().any(|e| e.pid == process.pid) {
+                    matches.push(process);
+                }
+            }
+            processes.matches = Some(matches);
+
+            let pgid_raw = self.event.clone().unwrap().pgid.unwrap();
+            let pgid = rustix::process::Pid::from_raw(pgid_raw).unwrap();
+            // The group may be gone already
+            let _ = kill_process_group(pgid, Signal::Term);
+            processes.terminate(&GRACE_PERIOD);
+
             self.status = Some(Status::Aborted);
             self.log()?;
         }
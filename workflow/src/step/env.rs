@@ -0,0 +1,98 @@
+// Structs
+use crate::types::{Fallback, Parallel, Step, StepOrParallel};
+use std::collections::HashMap;
+
+/**
+Merge a child scope environment over its parent scope environment.
+Child variables override parent variables.
+*/
+pub fn merge_env(
+    parent: &HashMap<String, String>,
+    child: &Option<HashMap<String, String>>,
+) -> HashMap<String, String> {
+    let mut env = parent.to_owned();
+    if let Some(child) = child {
+        env.extend(child.to_owned());
+    }
+    env
+}
+
+impl StepOrParallel {
+    /**
+    Resolve the environment of every underlying command
+    from the parent scope environment.
+    */
+    pub fn inherit_env(&mut self, parent: &HashMap<String, String>) {
+        match self {
+            StepOrParallel::Step(res) => res.inherit_env(parent),
+            StepOrParallel::Parallel(res) => res.inherit_env(parent),
+        }
+    }
+}
+impl Parallel {
+    pub fn inherit_env(&mut self, parent: &HashMap<String, String>) {
+        let env = merge_env(parent, &self.env);
+        for step in &mut self.steps {
+            step.inherit_env(&env);
+        }
+        if let Some(fallback) = self.fallback.as_mut() {
+            fallback.inherit_env(&env);
+        }
+    }
+}
+impl Step {
+    pub fn inherit_env(&mut self, parent: &HashMap<String, String>) {
+        let env = merge_env(parent, &self.env);
+        for command in &mut self.commands {
+            command.process.envs(&env);
+        }
+        if let Some(fallback) = self.fallback.as_mut() {
+            fallback.inherit_env(&env);
+        }
+    }
+}
+impl Fallback {
+    pub fn inherit_env(&mut self, parent: &HashMap<String, String>) {
+        for steps in [
+            &mut self.on_started,
+            &mut self.on_failure,
+            &mut self.on_success,
+            &mut self.on_abortion,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for step in steps {
+                step.inherit_env(parent);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{Command, Pipeline, Step, StepOrParallel};
+    use std::collections::HashMap;
+
+    #[test]
+    fn child_overrides_parent() {
+        let mut step = StepOrParallel::Step(Step {
+            commands: vec![Command::new("env")],
+            env: Some(HashMap::from([("FOO".to_owned(), "step".to_owned())])),
+            ..Step::default()
+        });
+        let pipeline = Pipeline {
+            env: Some(HashMap::from([
+                ("FOO".to_owned(), "pipeline".to_owned()),
+                ("BAR".to_owned(), "pipeline".to_owned()),
+            ])),
+            ..Pipeline::default()
+        };
+        step.inherit_env(&pipeline.env.unwrap());
+
+        let procs = step.get_procs().unwrap();
+        let env = procs[0].env.clone().unwrap();
+        assert_eq!(env.get("FOO"), Some(&"step".to_owned()));
+        assert_eq!(env.get("BAR"), Some(&"pipeline".to_owned()));
+    }
+}
@@ -1,5 +1,7 @@
 // Structs
-use crate::types::{Mode, Step, StepOrParallel};
+use crate::types::{Limits, Mode, Retry, Step, StepOrParallel};
+use pipelight_exec::dates::convert::iso8601_to_std_duration;
+use std::time;
 
 impl StepOrParallel {
     pub fn get_mode(&self) -> Option<Mode> {
@@ -18,3 +20,80 @@ impl Step {
         }
     }
 }
+impl Step {
+    /**
+    Return the step maximum execution time if any.
+    */
+    pub fn get_timeout(&self) -> Option<time::Duration> {
+        if let Some(options) = &self.options {
+            if let Some(timeout) = &options.timeout {
+                return iso8601_to_std_duration(timeout).ok();
+            }
+        }
+        None
+    }
+}
+impl Step {
+    /**
+    Return the step retry policy if any.
+    */
+    pub fn get_retry(&self) -> Option<Retry> {
+        if let Some(options) = &self.options {
+            options.retry.clone()
+        } else {
+            None
+        }
+    }
+}
+impl Step {
+    /**
+    Return the exit codes that are considered successful,
+    on top of the default zero.
+    */
+    pub fn get_allowed_exit_codes(&self) -> Vec<i32> {
+        if let Some(options) = &self.options {
+            options.allowed_exit_codes.clone().unwrap_or_default()
+        } else {
+            vec![]
+        }
+    }
+    /**
+    Return the step commands resource limits if any.
+    */
+    pub fn get_limits(&self) -> Option<Limits> {
+        self.options.as_ref().and_then(|e| e.limits.clone())
+    }
+    /**
+    Whether the step commands are run in a pseudo-terminal.
+    */
+    pub fn is_tty(&self) -> bool {
+        self.options.as_ref().and_then(|e| e.tty) == Some(true)
+    }
+}
+impl Retry {
+    /**
+    Return the delay to wait before the given attempt.
+    The backoff is doubled on every subsequent attempt.
+    */
+    pub fn get_delay(&self, attempt: u32) -> time::Duration {
+        if let Some(backoff) = &self.backoff {
+            if let Ok(backoff) = iso8601_to_std_duration(backoff) {
+                let factor = 2_u32.saturating_pow(attempt.saturating_sub(2));
+                return backoff.saturating_mul(factor);
+            }
+        }
+        time::Duration::ZERO
+    }
+    /**
+    Whether a failed attempt should be retried, given its exit code.
+    */
+    pub fn should_retry(&self, attempt: u32, exit_code: Option<i32>) -> bool {
+        if attempt >= self.attempts {
+            return false;
+        }
+        match &self.exit_codes {
+            Some(codes) => exit_code.is_some_and(|e| codes.contains(&e)),
+            None => true,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 // Structs
-use crate::types::{Fallback, Parallel, Step, StepOrParallel};
-use pipelight_exec::Process;
+use crate::types::{Fallback, Parallel, Skip, Step, StepOrParallel};
+use pipelight_exec::{Process, Usage};
 // Error Handling
 use miette::Result;
 
@@ -55,3 +55,80 @@ impl Fallback {
         Ok(procs)
     }
 }
+impl StepOrParallel {
+    /**
+    Return the names of the steps, a parallel contains many of them.
+    */
+    pub fn get_names(&self) -> Vec<String> {
+        match self {
+            StepOrParallel::Step(step) => vec![step.name.clone()],
+            StepOrParallel::Parallel(parallel) => {
+                parallel.steps.iter().map(|e| e.name.clone()).collect()
+            }
+        }
+    }
+    /**
+    Return the names of the steps that must succeed before this one starts.
+    Needs between steps of a same parallel are rejected when the config is loaded.
+    */
+    pub fn get_needs(&self) -> Vec<String> {
+        let names = self.get_names();
+        let mut needs: Vec<String> = vec![];
+        let steps = match self {
+            StepOrParallel::Step(step) => vec![step],
+            StepOrParallel::Parallel(parallel) => parallel.steps.iter().collect(),
+        };
+        for step in steps {
+            for need in step.needs.iter().flatten() {
+                if !names.contains(need) && !needs.contains(need) {
+                    needs.push(need.to_owned());
+                }
+            }
+        }
+        needs
+    }
+}
+impl StepOrParallel {
+    /**
+    Whether the step result is reused from a parent run,
+    in which case it must not be run again.
+    */
+    pub fn is_reused(&self) -> bool {
+        match self {
+            StepOrParallel::Step(step) => step.reused == Some(true),
+            StepOrParallel::Parallel(parallel) => {
+                parallel.steps.iter().all(|e| e.reused == Some(true))
+            }
+        }
+    }
+    /**
+    Whether the step has been left out of the run from the command line.
+    */
+    pub fn is_excluded(&self) -> bool {
+        match self {
+            StepOrParallel::Step(step) => step.skipped == Some(Skip::Excluded),
+            StepOrParallel::Parallel(parallel) => parallel
+                .steps
+                .iter()
+                .all(|e| e.skipped == Some(Skip::Excluded)),
+        }
+    }
+}
+impl StepOrParallel {
+    /**
+    Return the resources consumed by the steps run,
+    steps reused from a parent run are ignored.
+    */
+    pub fn get_usage(&self) -> Option<Usage> {
+        let steps = match self {
+            StepOrParallel::Step(step) => vec![step],
+            StepOrParallel::Parallel(parallel) => parallel.steps.iter().collect(),
+        };
+        Usage::aggregate(
+            steps
+                .iter()
+                .filter(|e| e.reused != Some(true))
+                .filter_map(|e| e.usage.as_ref()),
+        )
+    }
+}
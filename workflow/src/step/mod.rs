@@ -1,2 +1,5 @@
+mod env;
 mod execution_mode;
 mod getters;
+mod outputs;
+mod skip;
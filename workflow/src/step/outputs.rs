@@ -0,0 +1,138 @@
+// Structs
+use crate::types::{Step, StepOutput};
+use pipelight_exec::Status;
+use std::collections::BTreeMap;
+// Filesystem
+use std::fs;
+use std::path::PathBuf;
+// Error Handling
+use miette::{IntoDiagnostic, Result};
+// Regex
+use regex::Regex;
+
+/**
+The environment variable holding the path of the file
+commands can write `key=value` lines to, to set step outputs.
+*/
+pub const OUTPUT_ENV: &str = "PIPELIGHT_OUTPUT";
+
+impl Step {
+    /**
+    Create a fresh output file and pass its path to every step command.
+    The file lives in the first command outputs directory (.pipelight/proc/<uuid>/output),
+    so that it is kept and cleaned along with the run logs.
+    */
+    pub fn set_output_file(&mut self) -> Result<Option<PathBuf>> {
+        let path = match self.commands.first() {
+            Some(command) => {
+                let (stdout_path, _) = command.process.io.get_paths();
+                PathBuf::from(stdout_path).with_file_name("output")
+            }
+            None => return Ok(None),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).into_diagnostic()?;
+        }
+        fs::write(&path, "").into_diagnostic()?;
+        for command in &mut self.commands {
+            command.process.env(OUTPUT_ENV, &path.display().to_string());
+        }
+        Ok(Some(path))
+    }
+    /**
+    Resolve the step outputs from the commands stdout
+    and from the lines written to the output file.
+    The stdout is read from the outputs files when available,
+    as the one kept in the logs may be truncated.
+    */
+    pub fn capture_outputs(&mut self, path: &Option<PathBuf>) -> Result<()> {
+        let mut outputs = self.outputs.clone().unwrap_or_default();
+
+        // Stdout declared outputs
+        let stdout: String = self
+            .commands
+            .iter()
+            .filter(|e| e.process.state.status == Some(Status::Succeeded))
+            .filter_map(|e| {
+                let (stdout_path, _) = e.process.io.get_paths();
+                match fs::read(stdout_path) {
+                    Ok(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+                    Err(_) => e.process.io.stdout.clone(),
+                }
+            })
+            .collect();
+        for output in outputs.values_mut() {
+            if let Some(source) = &output.source {
+                output.value = capture(&stdout, source);
+            }
+        }
+
+        // Output file
+        if let Some(path) = path.as_ref().filter(|e| e.exists()) {
+            let file = fs::read_to_string(path).into_diagnostic()?;
+            for (key, value) in parse_output_file(&file) {
+                outputs.insert(
+                    key,
+                    StepOutput {
+                        source: None,
+                        value: Some(value),
+                    },
+                );
+            }
+        }
+
+        if !outputs.is_empty() {
+            self.outputs = Some(outputs);
+        }
+        Ok(())
+    }
+}
+
+/**
+Capture a value from the stdout,
+either its "last_line" or the first match of a regex (or of its first capture group).
+*/
+fn capture(stdout: &str, source: &str) -> Option<String> {
+    if source == "last_line" {
+        return stdout
+            .lines()
+            .rev()
+            .find(|e| !e.trim().is_empty())
+            .map(|e| e.to_owned());
+    }
+    let re = Regex::new(source).ok()?;
+    let caps = re.captures(stdout)?;
+    caps.get(1).or(caps.get(0)).map(|e| e.as_str().to_owned())
+}
+
+/**
+Parse `key=value` lines, ignoring blank and malformed lines.
+*/
+fn parse_output_file(file: &str) -> BTreeMap<String, String> {
+    file.lines()
+        .filter_map(|e| e.split_once('='))
+        .map(|(key, value)| (key.trim().to_owned(), value.to_owned()))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn capture_stdout() {
+        let stdout = "Compiling...\nversion: 1.2.3\nDone\n\n";
+        assert_eq!(capture(stdout, "last_line"), Some("Done".to_owned()));
+        assert_eq!(capture(stdout, r"version: (\S+)"), Some("1.2.3".to_owned()));
+        assert_eq!(capture(stdout, r"\d+\.\d+"), Some("1.2".to_owned()));
+        assert_eq!(capture(stdout, "missing"), None);
+    }
+    #[test]
+    fn output_file() {
+        let file = "version=1.2.3\nmalformed\nurl=https://example.com/?a=b\n";
+        let outputs = parse_output_file(file);
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs["url"], "https://example.com/?a=b");
+    }
+}
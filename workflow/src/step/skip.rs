@@ -0,0 +1,30 @@
+// Structs
+use crate::types::{Skip, Step, StepOrParallel};
+// Traits
+use pipelight_exec::{Statuable, Status};
+
+impl Step {
+    /**
+    Mark the step as skipped, and record why.
+    */
+    pub fn skip(&mut self, reason: Skip) {
+        self.status = Some(Status::Skipped);
+        self.skipped = Some(reason);
+    }
+}
+impl StepOrParallel {
+    /**
+    Mark the step, or every step of the parallel, as skipped.
+    */
+    pub fn skip(&mut self, reason: Skip) {
+        self.set_status(Some(Status::Skipped));
+        match self {
+            StepOrParallel::Step(step) => step.skip(reason),
+            StepOrParallel::Parallel(parallel) => {
+                for step in parallel.steps.iter_mut() {
+                    step.skip(reason.clone());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,272 @@
+// Structs
+use crate::types::{Duration, Pipeline, Step, StepOrParallel, Trigger};
+use pipelight_exec::{Statuable, Status};
+use regex::Regex;
+use std::fmt;
+// Dates
+use chrono::{DateTime, Local};
+
+/**
+A JUnit XML report of pipeline runs,
+for tools that read test results (IDEs, test dashboards...).
+- a pipeline run is a testsuite,
+- a step is a testcase,
+- the stderr of the failed commands of a step is its failure message,
+  or their stdout when they have no stderr (ex: merged by a pseudo-terminal).
+*/
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Junit {
+    pub pipelines: Vec<Pipeline>,
+}
+impl From<&Vec<Pipeline>> for Junit {
+    fn from(e: &Vec<Pipeline>) -> Self {
+        Junit {
+            pipelines: e.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for Junit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut string = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_owned();
+        string += "<testsuites>\n";
+        for pipeline in &self.pipelines {
+            string += &testsuite(pipeline);
+        }
+        string += "</testsuites>\n";
+        write!(f, "{}", string)
+    }
+}
+
+/**
+Convert a pipeline run into a testsuite element.
+*/
+fn testsuite(pipeline: &Pipeline) -> String {
+    let steps: Vec<&Step> = pipeline
+        .steps
+        .iter()
+        .flat_map(|e| match e {
+            StepOrParallel::Step(res) => vec![res],
+            StepOrParallel::Parallel(res) => res.steps.iter().collect(),
+        })
+        .collect();
+    let count = |statuses: &[Status]| {
+        steps
+            .iter()
+            .filter(|e| e.status.as_ref().is_some_and(|e| statuses.contains(e)))
+            .count()
+    };
+    let failures = count(&[Status::Failed, Status::TimedOut]);
+    let errors = count(&[Status::Aborted]);
+    let skipped = steps.len() - failures - errors - count(&[Status::Succeeded]);
+
+    let mut attributes = format!(
+        "name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\"",
+        escape(&pipeline.name),
+        steps.len(),
+        failures,
+        errors,
+        skipped,
+    );
+    if let Some(time) = get_seconds(&pipeline.duration) {
+        attributes += &format!(" time=\"{:.3}\"", time);
+    }
+    // JUnit timestamps have no timezone nor fractional seconds
+    let date = pipeline
+        .event
+        .as_ref()
+        .and_then(|e| e.date.parse::<DateTime<Local>>().ok());
+    if let Some(date) = date {
+        attributes += &format!(" timestamp=\"{}\"", date.format("%Y-%m-%dT%H:%M:%S"));
+    }
+
+    let mut string = format!("  <testsuite {}>\n", attributes);
+    string += "    <properties>\n";
+    let mut properties = vec![("uuid", pipeline.uuid.to_string())];
+    if let Some(event) = &pipeline.event {
+        let git_ref = match &event.trigger {
+            Trigger::TriggerBranch(e) => e.branch.clone().map(|e| ("branch", e)),
+            Trigger::TriggerTag(e) => e.tag.clone().map(|e| ("tag", e)),
+        };
+        properties.extend(git_ref);
+        if let Ok(Some(action)) = event.trigger.get_action() {
+            properties.push(("action", String::from(&action)));
+        }
+        if let Ok(Some(commit)) = event.trigger.get_commit() {
+            properties.push(("commit", commit));
+        }
+    }
+    for (name, value) in properties {
+        string += &format!(
+            "      <property name=\"{}\" value=\"{}\"/>\n",
+            name,
+            escape(&value)
+        );
+    }
+    string += "    </properties>\n";
+    for step in steps {
+        string += &testcase(&pipeline.name, step);
+    }
+    string += "  </testsuite>\n";
+    string
+}
+
+/**
+Convert a step into a testcase element.
+*/
+fn testcase(pipeline: &str, step: &Step) -> String {
+    let mut attributes = format!(
+        "name=\"{}\" classname=\"{}\"",
+        escape(&step.name),
+        escape(pipeline)
+    );
+    if let Some(time) = get_seconds(&step.duration) {
+        attributes += &format!(" time=\"{:.3}\"", time);
+    }
+    let result = match step.status {
+        Some(Status::Succeeded) => None,
+        Some(Status::Failed) | Some(Status::TimedOut) => {
+            let mut stderr = "".to_owned();
+            for command in &step.commands {
+                if command.get_status() != Some(Status::Failed)
+                    && command.get_status() != Some(Status::TimedOut)
+                {
+                    continue;
+                }
+                if let Some(stdin) = &command.process.io.stdin {
+                    stderr += &format!("$ {}\n", stdin);
+                }
+                let io = &command.process.io;
+                let text = match &io.stderr {
+                    Some(text) if !text.is_empty() => Some(text),
+                    _ => io.stdout.as_ref(),
+                };
+                if let Some(text) = text {
+                    stderr += text;
+                    stderr += "\n";
+                }
+            }
+            let (message, kind) = match step.status {
+                Some(Status::TimedOut) => ("step timed out", "timed-out"),
+                _ => ("step failed", "failed"),
+            };
+            // Strip terminal colors
+            let stderr = Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]")
+                .unwrap()
+                .replace_all(stderr.trim_end(), "");
+            Some(format!(
+                "      <failure message=\"{}\" type=\"{}\">{}</failure>\n",
+                message,
+                kind,
+                escape(&stderr)
+            ))
+        }
+        Some(Status::Aborted) => {
+            Some("      <error message=\"step aborted\" type=\"aborted\"/>\n".to_owned())
+        }
+        Some(Status::Started) | Some(Status::Running) => {
+            Some("      <skipped message=\"step still running\"/>\n".to_owned())
+        }
+        _ => Some("      <skipped/>\n".to_owned()),
+    };
+    match result {
+        Some(result) => format!("    <testcase {}>\n{}    </testcase>\n", attributes, result),
+        None => format!("    <testcase {}/>\n", attributes),
+    }
+}
+
+/**
+Return a stopped duration in seconds.
+*/
+fn get_seconds(duration: &Option<Duration>) -> Option<f64> {
+    let mut duration = duration.clone().filter(|e| e.ended_at.is_some())?;
+    duration.get().ok().map(|e| e.as_secs_f64())
+}
+
+/**
+Escape XML special characters,
+and strip the control characters XML doesn't allow.
+*/
+fn escape(text: &str) -> String {
+    let mut string = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => string += "&amp;",
+            '<' => string += "&lt;",
+            '>' => string += "&gt;",
+            '"' => string += "&quot;",
+            '\'' => string += "&apos;",
+            '\t' | '\n' | '\r' => string.push(c),
+            c if c.is_control() => {}
+            c => string.push(c),
+        }
+    }
+    string
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{Command, Event};
+
+    #[test]
+    fn failed_step() {
+        let mut command = Command::new("cargo test");
+        command.set_status(Some(Status::Failed));
+        command.process.io.stderr = Some("\x1b[31merror\x1b[0m: expected <T>".to_owned());
+        let pipeline = Pipeline {
+            name: "test".to_owned(),
+            status: Some(Status::Failed),
+            steps: vec![
+                StepOrParallel::Step(Step {
+                    name: "build".to_owned(),
+                    status: Some(Status::Succeeded),
+                    ..Default::default()
+                }),
+                StepOrParallel::Step(Step {
+                    name: "test".to_owned(),
+                    status: Some(Status::Failed),
+                    commands: vec![command],
+                    ..Default::default()
+                }),
+            ],
+            ..Default::default()
+        };
+        let xml = Junit::from(&vec![pipeline]).to_string();
+        assert!(xml.contains("tests=\"2\" failures=\"1\" errors=\"0\" skipped=\"0\""));
+        assert!(xml.contains("<testcase name=\"build\" classname=\"test\"/>"));
+        assert!(xml.contains(
+            "<failure message=\"step failed\" type=\"failed\">$ cargo test\nerror: expected &lt;T&gt;</failure>"
+        ));
+    }
+    #[test]
+    fn merged_outputs() {
+        // A pseudo-terminal merges the stderr into the stdout
+        let mut command = Command::new("cargo test");
+        command.set_status(Some(Status::Failed));
+        command.process.io.stdout = Some("error: failed".to_owned());
+        command.process.io.stderr = Some("".to_owned());
+        let step = Step {
+            name: "test".to_owned(),
+            status: Some(Status::Failed),
+            commands: vec![command],
+            ..Default::default()
+        };
+        assert!(testcase("test", &step).contains("$ cargo test\nerror: failed</failure>"));
+    }
+    #[test]
+    fn timestamp() {
+        let pipeline = Pipeline {
+            name: "test".to_owned(),
+            event: Some(Event {
+                date: "2024-05-01 12:30:45.123456789 +02:00".to_owned(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let date = "2024-05-01T10:30:45Z".parse::<DateTime<Local>>().unwrap();
+        let timestamp = format!("timestamp=\"{}\"", date.format("%Y-%m-%dT%H:%M:%S"));
+        let xml = Junit::from(&vec![pipeline]).to_string();
+        assert!(xml.contains(&timestamp));
+    }
+}
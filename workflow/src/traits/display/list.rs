@@ -45,17 +45,20 @@ impl PipelineTable {
             git_ref: None,
             commit: None,
         };
-        // Try to retrieve info from last logs
-        let log = Logs::get_by_name(&e.name);
-        if let Ok(log) = log {
-            if let Some(event) = log.event.clone() {
+        // Try to retrieve info from the last run in the logs index
+        let entry = Logs::get_last_entry_by_name(&e.name);
+        if let Ok(entry) = entry {
+            if let Some(event) = entry.event.clone() {
                 table.date = Some(event.date);
                 table.action = event.trigger.get_action()?;
                 table.git_ref = event.trigger.get_ref()?;
                 table.commit = event.trigger.get_commit()?;
             }
-            table.status = log.status.clone();
-            // table.last_trigger = log.triggers.clone();
+            table.status = entry.status.clone();
+            // Unreported aborted run
+            if table.status == Some(Status::Running) && !entry.is_running() {
+                table.status = Some(Status::Aborted);
+            }
         }
         Ok(table)
     }
@@ -1,5 +1,8 @@
 mod characters;
+mod junit;
 mod list;
+mod stats;
 mod tree;
 
+pub use junit::Junit;
 pub use tree::set_override;
@@ -0,0 +1,111 @@
+// Types
+use crate::{PipelineStats, Stats, StepStats};
+use pipelight_exec::dates::convert::std_duration_to_human_duration;
+use std::time;
+
+use owo_colors::OwoColorize;
+use tabled::{settings::Style, Table, Tabled};
+// Error Handling
+use miette::Result;
+
+#[derive(Debug, Clone, Eq, PartialEq, Tabled)]
+pub struct PipelineStatsTable {
+    pub name: String,
+    pub runs: usize,
+    pub failures: usize,
+    pub success: String,
+    pub p50: String,
+    pub p95: String,
+    #[tabled(rename = "flaky steps")]
+    pub flaky_steps: usize,
+}
+impl From<&PipelineStats> for PipelineStatsTable {
+    fn from(e: &PipelineStats) -> Self {
+        PipelineStatsTable {
+            name: e.name.clone(),
+            runs: e.runs,
+            failures: e.failures,
+            success: display_rate(&e.success_rate),
+            p50: display_millis(&e.duration_p50),
+            p95: display_millis(&e.duration_p95),
+            flaky_steps: e
+                .steps
+                .iter()
+                .filter(|e| !e.flaky_commits.is_empty())
+                .count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Tabled)]
+pub struct StepStatsTable {
+    pub pipeline: String,
+    pub step: String,
+    pub runs: usize,
+    pub failures: usize,
+    pub success: String,
+    pub p50: String,
+    pub p95: String,
+    pub flaky: String,
+}
+impl From<&StepStats> for StepStatsTable {
+    fn from(e: &StepStats) -> Self {
+        let flaky = match e.flaky_commits.len() {
+            0 => "-".white().to_string(),
+            1 => "on 1 commit".yellow().to_string(),
+            n => format!("on {} commits", n).yellow().to_string(),
+        };
+        StepStatsTable {
+            pipeline: e.pipeline.clone(),
+            step: e.name.clone(),
+            runs: e.runs,
+            failures: e.failures,
+            success: display_rate(&e.success_rate),
+            p50: display_millis(&e.duration_p50),
+            p95: display_millis(&e.duration_p95),
+            flaky,
+        }
+    }
+}
+
+impl Stats {
+    /**
+    Print the pipelines statistics,
+    and the steps statistics with the most frequently failing steps first.
+    */
+    pub fn display(&self) -> Result<()> {
+        let pipelines: Vec<PipelineStatsTable> = self
+            .pipelines
+            .iter()
+            .map(PipelineStatsTable::from)
+            .collect();
+        let mut res = Table::new(&pipelines);
+        res.with(Style::rounded());
+        println!("{}", res);
+
+        let steps: Vec<StepStatsTable> = self
+            .get_failing_steps()
+            .iter()
+            .map(StepStatsTable::from)
+            .collect();
+        let mut res = Table::new(&steps);
+        res.with(Style::rounded());
+        println!("{}", res);
+        Ok(())
+    }
+}
+
+pub fn display_rate(e: &Option<f64>) -> String {
+    match e {
+        Some(rate) => format!("{:.1}%", rate * 100.0),
+        None => "-".white().to_string(),
+    }
+}
+pub fn display_millis(e: &Option<u64>) -> String {
+    match e {
+        Some(millis) => {
+            std_duration_to_human_duration(time::Duration::from_millis(*millis)).unwrap_or_default()
+        }
+        None => "-".white().to_string(),
+    }
+}
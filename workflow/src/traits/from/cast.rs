@@ -1,10 +1,13 @@
 use crate::pipeline::Filters;
 use crate::types::{
-    Command, Config, ConfigOpts, Fallback, Mode, Parallel, Pipeline, PipelineOpts, Step, StepOpts,
-    StepOrParallel,
+    Command, Config, ConfigOpts, Fallback, Limits, LogsOpts, Mode, Parallel, Pipeline,
+    PipelineOpts, Retry, Step, StepOpts, StepOrParallel, StepOutput,
 };
 use crate::types::{Trigger, TriggerBranch, TriggerTag};
+// Matrix
+use super::matrix::{expand_matrix, get_aliases, resolve_needs};
 use log::LevelFilter;
+use pipelight_exec::dates::convert::iso8601_to_std_duration;
 use pipelight_exec::Process;
 
 use convert_case::{Case, Casing};
@@ -27,9 +30,21 @@ impl From<&cast::ConfigOpts> for ConfigOpts {
         if let Some(attach) = e.attach {
             options.attach = Some(attach);
         }
+        if let Some(logs) = &e.logs {
+            options.logs = Some(LogsOpts::from(logs));
+        }
         options
     }
 }
+impl From<&cast::LogsOpts> for LogsOpts {
+    fn from(e: &cast::LogsOpts) -> Self {
+        LogsOpts {
+            keep: e.keep,
+            max_age: e.max_age.as_ref().map(|e| check_duration("max_age", e)),
+            max_size: e.max_size.as_ref().map(check_size),
+        }
+    }
+}
 impl From<&cast::Config> for Config {
     fn from(e: &cast::Config) -> Self {
         let mut options = None;
@@ -66,6 +81,12 @@ impl From<&cast::PipelineOpts> for PipelineOpts {
         if let Some(attach) = e.attach {
             options.attach = Some(attach);
         }
+        if let Some(timeout) = &e.timeout {
+            options.timeout = Some(check_duration("timeout", timeout));
+        }
+        if let Some(max_output) = &e.max_output {
+            options.max_output = Some(check_size(max_output));
+        }
         options
     }
 }
@@ -73,17 +94,29 @@ impl From<&cast::PipelineOpts> for PipelineOpts {
 impl From<&cast::Pipeline> for Pipeline {
     fn from(e: &cast::Pipeline) -> Self {
         // Convert steps
-        let steps = &e
+        let mut steps = e
             .steps
             .iter()
             .map(StepOrParallel::from)
             .collect::<Vec<StepOrParallel>>();
+        resolve_needs(&mut steps, &get_aliases(&e.steps));
 
         // Convert fallback
         let mut fallback = None;
         if e.fallback.is_some() {
             fallback = Some(Fallback::from(e.fallback.as_ref().unwrap()));
         }
+
+        // Resolve every command environment
+        // from the pipeline environment down to the step environment.
+        let env = e.env.clone().unwrap_or_default();
+        for step in &mut steps {
+            step.inherit_env(&env);
+        }
+        if let Some(fallback) = fallback.as_mut() {
+            fallback.inherit_env(&env);
+        }
+
         // Options
         let mut options = None;
         if let Some(cast_options) = &e.options {
@@ -108,7 +141,8 @@ impl From<&cast::Pipeline> for Pipeline {
         Pipeline {
             uuid: Uuid::new_v4(),
             name: e.name.to_owned(),
-            steps: steps.to_owned(),
+            steps,
+            env: e.env.clone(),
             triggers,
             fallback,
             options,
@@ -120,6 +154,13 @@ impl From<&cast::Pipeline> for Pipeline {
 impl From<&cast::StepOrParallel> for StepOrParallel {
     fn from(e: &cast::StepOrParallel) -> Self {
         match e {
+            // Matrix steps are expanded into parallel steps
+            cast::StepOrParallel::Step(res) if res.matrix.is_some() => {
+                StepOrParallel::Parallel(Parallel {
+                    steps: expand_matrix(res).iter().map(Step::from).collect(),
+                    ..Parallel::default()
+                })
+            }
             cast::StepOrParallel::Step(res) => StepOrParallel::Step(Step::from(res)),
             cast::StepOrParallel::Parallel(res) => StepOrParallel::Parallel(Parallel::from(res)),
         }
@@ -132,10 +173,89 @@ impl From<&cast::StepOpts> for StepOpts {
         if let Some(mode) = &e.mode {
             options.mode = Some(Mode::from(mode));
         }
+        if let Some(timeout) = &e.timeout {
+            options.timeout = Some(check_duration("timeout", timeout));
+        }
+        if let Some(retry) = &e.retry {
+            options.retry = Some(Retry::from(retry));
+        }
+        options.allowed_exit_codes = e.allowed_exit_codes.clone();
+        if let Some(limits) = &e.limits {
+            options.limits = Some(convert_limits(limits));
+        }
+        options.tty = e.tty;
         options
     }
 }
 
+/**
+Convert the config limits into process limits.
+(Both types are foreign to this crate, hence no From implementation)
+*/
+fn convert_limits(e: &cast::Limits) -> Limits {
+    Limits {
+        memory: e.memory.as_ref().map(check_size),
+        cpu: e.cpu,
+        open_files: e.open_files,
+        file_size: e.file_size.as_ref().map(check_size),
+    }
+}
+
+impl From<&cast::Retry> for Retry {
+    fn from(e: &cast::Retry) -> Self {
+        Retry {
+            attempts: e.attempts,
+            backoff: e.backoff.as_ref().map(|e| check_duration("backoff", e)),
+            exit_codes: e.exit_codes.clone(),
+        }
+    }
+}
+
+/**
+Ensure the provided duration is a valid ISO8601 duration.
+*/
+fn check_duration(field: &str, duration: &String) -> String {
+    match iso8601_to_std_duration(duration) {
+        Ok(_) => duration.to_owned(),
+        Err(_) => {
+            let message = format!(
+                "The {} {} is not a valid ISO8601 duration (ex: \"PT1M30S\")",
+                field, duration
+            );
+            error!("{}", message);
+            exit(1);
+        }
+    }
+}
+
+/**
+Convert a size with an optional binary unit (ex: "512M", "2G") into bytes.
+*/
+fn check_size(size: &String) -> u64 {
+    let text = size.trim().to_uppercase();
+    let text = text.trim_end_matches("IB").trim_end_matches('B');
+    let (number, unit) = match text.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => text.split_at(i),
+        None => (text, ""),
+    };
+    let multiplier: Option<u64> = match unit.trim() {
+        "" => Some(1),
+        "K" => Some(1 << 10),
+        "M" => Some(1 << 20),
+        "G" => Some(1 << 30),
+        "T" => Some(1 << 40),
+        _ => None,
+    };
+    match (number.parse::<u64>(), multiplier) {
+        (Ok(number), Some(multiplier)) => number.saturating_mul(multiplier),
+        _ => {
+            let message = format!("The size {} is not valid (ex: \"512M\", \"2G\")", size);
+            error!("{}", message);
+            exit(1);
+        }
+    }
+}
+
 impl From<&cast::Step> for Step {
     fn from(e: &cast::Step) -> Self {
         let commands = e
@@ -156,9 +276,29 @@ impl From<&cast::Step> for Step {
             options = Some(StepOpts::from(e.options.as_ref().unwrap()));
         }
 
+        // Convert output declarations
+        let outputs = e.outputs.as_ref().map(|outputs| {
+            outputs
+                .iter()
+                .map(|(name, source)| {
+                    let output = StepOutput {
+                        source: Some(source.to_owned()),
+                        value: None,
+                    };
+                    (name.to_owned(), output)
+                })
+                .collect()
+        });
+
         Step {
             name: e.clone().name,
             commands,
+            env: e.env.clone(),
+            needs: e.needs.clone(),
+            when: e.when.clone(),
+            outputs,
+            workdir: e.workdir.clone(),
+            shell: e.shell.clone(),
             fallback,
             options,
             ..Step::default()
@@ -175,10 +315,11 @@ impl From<&cast::Parallel> for Parallel {
         let mut res = Parallel {
             fallback,
             steps: vec![],
+            env: e.env.clone(),
             ..Parallel::default()
         };
         for step in &e.parallel {
-            res.steps.push(Step::from(step));
+            res.steps.extend(expand_matrix(step).iter().map(Step::from));
         }
         res
     }
@@ -0,0 +1,183 @@
+// Structs
+use crate::types::StepOrParallel;
+use std::collections::{BTreeMap, HashMap};
+// Interpolation
+use convert_case::{Case, Casing};
+use regex::{Captures, Regex};
+
+/**
+Expand a step declaring a matrix of variables
+into one concrete step per combination of values.
+
+Matrix variables are interpolated into the step name, commands and env
+wherever `${{ matrix.<key> }}` is found, and passed to the commands
+as `MATRIX_<KEY>` environment variables.
+A step without a matrix is returned untouched.
+*/
+pub fn expand_matrix(e: &cast::Step) -> Vec<cast::Step> {
+    let matrix = match &e.matrix {
+        Some(matrix) => matrix,
+        None => return vec![e.to_owned()],
+    };
+    let mut steps = vec![];
+    for cell in get_cells(matrix) {
+        let mut step = e.to_owned();
+        step.matrix = None;
+
+        // Name
+        // Values that are not part of the name are appended to keep cell names unique.
+        step.name = interpolate(&e.name, &cell);
+        let values = cell
+            .iter()
+            .filter(|(key, _)| !get_keys(&e.name).contains(key))
+            .map(|(_, value)| value.to_owned())
+            .collect::<Vec<String>>();
+        if !values.is_empty() {
+            step.name = format!("{} ({})", step.name, values.join(", "));
+        }
+        // Commands
+        step.commands = e.commands.iter().map(|e| interpolate(e, &cell)).collect();
+        // Env
+        let mut env: HashMap<String, String> = e
+            .env
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.to_owned(), interpolate(v, &cell)))
+            .collect();
+        for (key, value) in &cell {
+            let key = format!("matrix_{}", key).to_case(Case::UpperSnake);
+            env.insert(key, value.to_owned());
+        }
+        step.env = Some(env);
+
+        steps.push(step);
+    }
+    steps
+}
+
+/**
+Return every combination of the matrix values.
+*/
+fn get_cells(matrix: &BTreeMap<String, Vec<String>>) -> Vec<BTreeMap<String, String>> {
+    let mut cells = vec![BTreeMap::new()];
+    for (key, values) in matrix {
+        let mut next = vec![];
+        for cell in &cells {
+            for value in values {
+                let mut cell = cell.to_owned();
+                cell.insert(key.to_owned(), value.to_owned());
+                next.push(cell);
+            }
+        }
+        cells = next;
+    }
+    cells
+}
+
+fn get_regex() -> Regex {
+    Regex::new(r"\$\{\{\s*matrix\.(\w+)\s*\}\}").unwrap()
+}
+
+/**
+Return the matrix keys referenced in the text.
+*/
+fn get_keys(text: &str) -> Vec<String> {
+    get_regex()
+        .captures_iter(text)
+        .map(|caps| caps[1].to_owned())
+        .collect()
+}
+
+/**
+Replace the `${{ matrix.<key> }}` placeholders with the cell values.
+Unknown placeholders are left as is.
+*/
+fn interpolate(text: &str, cell: &BTreeMap<String, String>) -> String {
+    get_regex()
+        .replace_all(text, |caps: &Captures| match cell.get(&caps[1]) {
+            Some(value) => value.to_owned(),
+            None => caps[0].to_owned(),
+        })
+        .into_owned()
+}
+
+/**
+Return the names of the expanded steps for every step declaring a matrix.
+*/
+pub fn get_aliases(steps: &[cast::StepOrParallel]) -> HashMap<String, Vec<String>> {
+    let mut aliases = HashMap::new();
+    let mut insert = |step: &cast::Step| {
+        if step.matrix.is_some() {
+            let names = expand_matrix(step).into_iter().map(|e| e.name).collect();
+            aliases.insert(step.name.to_owned(), names);
+        }
+    };
+    for step in steps {
+        match step {
+            cast::StepOrParallel::Step(res) => insert(res),
+            cast::StepOrParallel::Parallel(res) => res.parallel.iter().for_each(&mut insert),
+        }
+    }
+    aliases
+}
+
+/**
+Make steps needing a matrix step need every one of its cells.
+*/
+pub fn resolve_needs(steps: &mut [StepOrParallel], aliases: &HashMap<String, Vec<String>>) {
+    for step in steps {
+        let steps = match step {
+            StepOrParallel::Step(res) => vec![res],
+            StepOrParallel::Parallel(res) => res.steps.iter_mut().collect(),
+        };
+        for step in steps {
+            if let Some(needs) = &step.needs {
+                let needs = needs
+                    .iter()
+                    .flat_map(|e| aliases.get(e).cloned().unwrap_or(vec![e.to_owned()]))
+                    .collect();
+                step.needs = Some(needs);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expand() {
+        let json = r#"
+        {
+            "name": "build ${{ matrix.target }}",
+            "commands": ["cargo build --target ${{ matrix.target }} --profile ${{matrix.profile}}"],
+            "matrix": { "target": ["x86_64", "aarch64"], "profile": ["dev", "release"] }
+        }
+        "#;
+        let step = serde_json::from_str::<cast::Step>(json).unwrap();
+        let steps = expand_matrix(&step);
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[0].name, "build x86_64 (dev)");
+        assert_eq!(
+            steps[0].commands[0],
+            "cargo build --target x86_64 --profile dev"
+        );
+        let env = steps[3].env.clone().unwrap();
+        assert_eq!(env.get("MATRIX_TARGET"), Some(&"aarch64".to_owned()));
+        assert_eq!(env.get("MATRIX_PROFILE"), Some(&"release".to_owned()));
+    }
+    #[test]
+    fn default_names() {
+        let json = r#"
+        {
+            "name": "test",
+            "commands": ["cargo test"],
+            "matrix": { "os": ["linux", "macos"] }
+        }
+        "#;
+        let step = serde_json::from_str::<cast::Step>(json).unwrap();
+        let names: Vec<String> = expand_matrix(&step).into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["test (linux)", "test (macos)"]);
+    }
+}
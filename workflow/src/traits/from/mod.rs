@@ -1,2 +1,3 @@
 mod cast;
+mod matrix;
 mod node;
@@ -4,7 +4,7 @@ Every pipeline subcomponent such as step and commands are converted to
 the node pretty printable type.
 */
 // Struct
-use crate::types::{Command, Event, Node, Parallel, Pipeline, Step, StepOrParallel, Trigger};
+use crate::types::{Command, Event, Node, Parallel, Pipeline, Skip, Step, StepOrParallel, Trigger};
 use log::LevelFilter;
 use pipelight_exec::{Statuable, Status};
 use pipelight_utils::git::Flag;
@@ -88,6 +88,16 @@ impl From<&Pipeline> for Node {
         head.push_str(&name);
         let mut children: Vec<Node> = e.steps.iter().map(Node::from).collect();
 
+        // Resource usage
+        if let Some(usage) = &e.usage {
+            children.push(Node {
+                value: Some(format!("usage: {}", usage)),
+                status: e.status.clone(),
+                level: LevelFilter::Debug,
+                ..Node::default()
+            });
+        }
+
         // Duration
         // If pipeline is_running
         let mut duration: Option<String> = None;
@@ -200,9 +210,69 @@ impl From<&Parallel> for Node {
 }
 impl From<&Step> for Node {
     fn from(e: &Step) -> Self {
-        let head = format!("step: {}", e.name.clone());
+        let mut head = format!("step: {}", e.name.clone());
+        if e.reused == Some(true) {
+            head.push_str(" (reused)");
+        }
         let mut children: Vec<Node> = e.commands.iter().map(Node::from).collect();
 
+        // Outputs
+        for (name, output) in e.outputs.iter().flatten() {
+            if let Some(value) = &output.value {
+                children.push(Node {
+                    value: Some(format!("output: {} = {}", name, value)),
+                    status: e.status.clone(),
+                    level: LevelFilter::Info,
+                    ..Node::default()
+                });
+            }
+        }
+
+        // Exceeded limits
+        for diag in e.commands.iter().filter_map(|c| c.get_limit_error(&e.name)) {
+            children.push(Node {
+                value: Some(format!(
+                    "{}\nhelp: {}",
+                    diag.message,
+                    diag.help.unwrap_or_default()
+                )),
+                status: e.status.clone(),
+                level: LevelFilter::Error,
+                ..Node::default()
+            });
+        }
+
+        // Resource usage
+        if let Some(usage) = &e.usage {
+            children.push(Node {
+                value: Some(format!("usage: {}", usage)),
+                status: e.status.clone(),
+                level: LevelFilter::Debug,
+                ..Node::default()
+            });
+        }
+
+        // Condition
+        if e.status == Some(Status::Skipped) {
+            let reason = match e.skipped {
+                Some(Skip::Excluded) => "excluded from the run".to_owned(),
+                Some(Skip::NeedFailed) => format!(
+                    "a needed step didn't succeed: {}",
+                    e.needs.clone().unwrap_or_default().join(", ")
+                ),
+                Some(Skip::ConditionFalse) => {
+                    format!("condition not met: {}", e.when.clone().unwrap_or_default())
+                }
+                None => "skipped".to_owned(),
+            };
+            children = vec![Node {
+                value: Some(reason),
+                status: e.status.clone(),
+                level: LevelFilter::Info,
+                ..Node::default()
+            }];
+        }
+
         // Duration
         let mut duration: Option<String> = None;
         if e.duration.is_some() {
@@ -279,6 +349,8 @@ impl From<&Command> for Node {
             let out = match e.get_status() {
                 Some(Status::Succeeded) => e.process.io.stdout.clone(),
                 Some(Status::Failed) => e.process.io.stderr.clone(),
+                Some(Status::TimedOut) => e.process.io.stderr.clone(),
+                Some(Status::Skipped) => None,
                 Some(Status::Started) => None,
                 Some(Status::Aborted) => None,
                 Some(Status::Running) => None,
@@ -310,8 +382,34 @@ impl From<&Command> for Node {
             } else {
                 node.children = Some(vec![stdout, stderr]);
             }
+            // Binary outputs are not printable
+            if e.process.io.is_binary == Some(true) {
+                let (stdout_path, _) = e.process.io.get_paths();
+                let path = stdout_path.trim_end_matches("/1");
+                node.children = Some(vec![Node {
+                    value: Some(format!("binary output, see {}", path)),
+                    status: e.get_status(),
+                    children: None,
+                    level: LevelFilter::Debug,
+                    ..Node::default()
+                }]);
+            }
+        }
+        node.value = e.get_template();
+        if let (Some(value), Some(attempt)) = (&node.value, e.attempt) {
+            node.value = Some(format!("{} (attempt {})", value, attempt));
+        }
+        // Exit code, terminating signal or exceeded limit
+        let state = &e.process.state;
+        let termination = match (state.exit_code, state.signal) {
+            _ if state.exceeded.is_some() => state.exceeded.as_ref().map(|e| e.to_string()),
+            (_, Some(signal)) => Some(format!("signal {}", signal)),
+            (Some(code), None) if code != 0 => Some(format!("exit code {}", code)),
+            _ => None,
+        };
+        if let (Some(value), Some(termination)) = (&node.value, termination) {
+            node.value = Some(format!("{} ({})", value, termination));
         }
-        node.value = e.process.io.stdin.clone();
         node.status = e.get_status();
         node
     }
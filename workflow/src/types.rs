@@ -2,12 +2,16 @@
 use log::LevelFilter;
 pub use pipelight_exec::dates::Duration;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
 // Structs
-use pipelight_exec::Process;
+pub use pipelight_exec::Limits;
 pub use pipelight_exec::Status;
+use pipelight_exec::{Process, Usage};
 use pipelight_utils::git::{Flag, Special};
+// Error Handling
+use miette::MietteDiagnostic;
 
 // Event - Process
 use chrono::Local;
@@ -25,6 +29,22 @@ pub struct ConfigOpts {
     // when triggered by a git hook.
     pub attach: Option<bool>,
     pub log_level: Option<LevelFilter>,
+    // The pipelines logs retention policy
+    pub logs: Option<LogsOpts>,
+}
+
+/**
+* Logs retention policy.
+* Runs are pruned when any of the limits is exceeded.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct LogsOpts {
+    // The number of runs to keep per pipeline
+    pub keep: Option<usize>,
+    // The maximum age of a run (iso8601 duration string)
+    pub max_age: Option<String>,
+    // The maximum total size in bytes of the logs
+    pub max_size: Option<u64>,
 }
 
 #[derive(Default, Debug, Clone, Eq, PartialEq)]
@@ -62,6 +82,10 @@ pub struct PipelineOpts {
     // when triggered by a git hook.
     pub attach: Option<bool>,
     pub log_level: Option<LevelFilter>,
+    // The maximum pipeline execution time (iso8601 duration string)
+    pub timeout: Option<String>,
+    // The maximum size in bytes of a command stdout and stderr kept in the logs
+    pub max_output: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
@@ -75,6 +99,12 @@ pub struct Pipeline {
     pub fallback: Option<Fallback>,
     pub steps: Vec<StepOrParallel>,
     pub options: Option<PipelineOpts>,
+    // Environment variables inherited by every step
+    pub env: Option<HashMap<String, String>>,
+    // The uuid of the run this one has been retried from
+    pub parent: Option<Uuid>,
+    // The resources consumed by every command run
+    pub usage: Option<Usage>,
 }
 impl Default for Pipeline {
     fn default() -> Self {
@@ -89,6 +119,9 @@ impl Default for Pipeline {
             options: None,
             steps,
             fallback: None,
+            env: None,
+            parent: None,
+            usage: None,
         }
     }
 }
@@ -98,8 +131,33 @@ pub struct StepOpts {
     // The step's command execution behavior
     // Failure Handling mode
     pub mode: Option<Mode>,
+    // The maximum step execution time (iso8601 duration string)
+    pub timeout: Option<String>,
+    // Retry policy for failing commands
+    pub retry: Option<Retry>,
+    // Exit codes that are considered successful
+    pub allowed_exit_codes: Option<Vec<i32>>,
+    // Resources the step commands can consume
+    pub limits: Option<Limits>,
+    // Whether the commands are run in a pseudo-terminal,
+    // which merges their stderr into their stdout
+    pub tty: Option<bool>,
 }
 
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Retry {
+    // The maximum number of attempts (including the first one).
+    pub attempts: u32,
+    // The delay before the first retry (iso8601 duration string),
+    // doubled on every subsequent attempt.
+    pub backoff: Option<String>,
+    // Only retry on those exit codes
+    pub exit_codes: Option<Vec<i32>>,
+}
+
+// Steps are built once from the config and rarely moved,
+// so they are kept inline rather than boxed.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 #[serde(untagged)]
 pub enum StepOrParallel {
@@ -118,6 +176,8 @@ pub struct Parallel {
     pub status: Option<Status>,
     pub duration: Option<Duration>,
     pub steps: Vec<Step>,
+    // Environment variables inherited by every parallel step
+    pub env: Option<HashMap<String, String>>,
     // Fallback Hooks
     pub fallback: Option<Fallback>,
 }
@@ -127,6 +187,7 @@ impl Default for Parallel {
             status: None,
             duration: None,
             steps: vec![Step::default()],
+            env: None,
             fallback: None,
         }
     }
@@ -138,6 +199,24 @@ pub struct Step {
     pub status: Option<Status>,
     pub duration: Option<Duration>,
     pub commands: Vec<Command>,
+    // Environment variables inherited by every command
+    pub env: Option<HashMap<String, String>>,
+    // Names of the steps that must succeed before this one starts
+    pub needs: Option<Vec<String>>,
+    // A condition expression, the step is skipped when false
+    pub when: Option<String>,
+    // Named values captured from the step stdout or its output file
+    pub outputs: Option<BTreeMap<String, StepOutput>>,
+    // The directory commands are run in
+    pub workdir: Option<String>,
+    // The shell program and its arguments commands are run with
+    pub shell: Option<Vec<String>>,
+    // The resources consumed by the step commands
+    pub usage: Option<Usage>,
+    // Whether the step result is reused from a parent run
+    pub reused: Option<bool>,
+    // Why the step has been skipped
+    pub skipped: Option<Skip>,
     // Failure Handling mode
     pub options: Option<StepOpts>,
     // Fallback Hooks
@@ -151,12 +230,28 @@ impl Default for Step {
             status: None,
             duration: None,
             commands,
+            env: None,
+            needs: None,
+            when: None,
+            outputs: None,
+            workdir: None,
+            shell: None,
+            usage: None,
+            reused: None,
+            skipped: None,
             options: None,
             fallback: None,
         }
     }
 }
 
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct StepOutput {
+    // "last_line" or a regex, None when written to the output file
+    pub source: Option<String>,
+    pub value: Option<String>,
+}
+
 #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct Fallback {
     pub on_started: Option<Vec<StepOrParallel>>,
@@ -165,6 +260,20 @@ pub struct Fallback {
     pub on_abortion: Option<Vec<StepOrParallel>>,
 }
 
+/**
+* The reason a step has been skipped.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Skip {
+    // Left out of the run from the command line
+    Excluded,
+    // A needed step didn't succeed
+    NeedFailed,
+    // The step condition is false
+    ConditionFalse,
+}
+
 #[derive(Debug, EnumIter, Serialize, Deserialize, Clone, PartialEq, PartialOrd, Eq, Ord)]
 #[serde(untagged)]
 pub enum Mode {
@@ -179,6 +288,10 @@ pub struct Command {
     pub duration: Option<Duration>,
     // Things relevant to unix process
     pub process: Process,
+    // The attempt number when the step is retried
+    pub attempt: Option<u32>,
+    // The command as written, before its placeholders are resolved into the process stdin
+    pub template: Option<String>,
 }
 impl Command {
     pub fn new(stdin: &str) -> Command {
@@ -187,6 +300,30 @@ impl Command {
             ..Command::default()
         }
     }
+    /**
+    Return the command as written,
+    the process stdin until the command has been run.
+    */
+    pub fn get_template(&self) -> Option<String> {
+        self.template.clone().or(self.process.io.stdin.clone())
+    }
+    /**
+    Return a diagnostic when the command has been terminated
+    for exceeding one of its step limits.
+    */
+    pub fn get_limit_error(&self, step: &str) -> Option<MietteDiagnostic> {
+        let limit = self.process.state.exceeded.as_ref()?;
+        let message = format!(
+            "The step \"{}\" command {:?} has been terminated: {}",
+            step,
+            self.get_template().unwrap_or_default(),
+            limit
+        );
+        let diag = MietteDiagnostic::new(message)
+            .with_code("workflow::step::limit")
+            .with_help("Raise the step limits or reduce the command consumption");
+        Some(diag)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, Ord, PartialEq, PartialOrd)]